@@ -1,55 +1,73 @@
 use proc_macro2::{Span, TokenStream};
 use quote::quote;
-use syn::Ident;
+use syn::{Ident, ext::IdentExt};
 
-use crate::ir::{self, Expr, FieldKind, LeafKind, MapKey};
+use crate::ir::{self, ConstType, Expr, FieldKind, LeafKind, MapKey};
 
 
 
 /// Generates the whole `const META: ... = ...;` item.
 pub(super) fn gen(input: &ir::Input) -> TokenStream {
-    fn env_tokens(env: &Option<String>) -> TokenStream {
-        match env {
-            Some(key) => quote! { std::option::Option::Some(#key) },
+    fn opt_str_tokens(s: &Option<String>) -> TokenStream {
+        match s {
+            Some(s) => quote! { std::option::Option::Some(#s) },
+            None => quote! { std::option::Option::None },
+        }
+    }
+
+    fn opt_expr_tokens(expr: Option<&Expr>, ty: &syn::Type) -> TokenStream {
+        match expr {
+            Some(expr) => {
+                let meta = default_value_to_meta_expr(expr, Some(ty));
+                quote! { std::option::Option::Some(#meta) }
+            }
             None => quote! { std::option::Option::None },
         }
     }
 
     let name_str = input.name.to_string();
     let doc = &input.doc;
-    let meta_fields = input.fields.iter().map(|f| {
-        let name = f.name.to_string();
+    let meta_fields = input.fields.iter().filter(|f| !f.is_extend()).map(|f| {
+        let name = f.name.unraw().to_string();
         let doc =  &f.doc;
+        let hidden_in_template = f.no_template;
         let kind = match &f.kind {
-            FieldKind::Nested { ty } => {
+            FieldKind::Nested { ty, partial: None, .. } => {
                 quote! {
                     confique::meta::FieldKind::Nested { meta: &<#ty as confique::Config>::META }
                 }
             }
-            FieldKind::Leaf { env, kind: LeafKind::Optional { .. }, ..} => {
-                let env = env_tokens(env);
+            FieldKind::Nested { partial: Some(partial), .. } => {
                 quote! {
-                    confique::meta::FieldKind::Leaf {
-                        env: #env,
-                        kind: confique::meta::LeafKind::Optional,
+                    confique::meta::FieldKind::Nested {
+                        meta: &<#partial as confique::FieldPartial>::META,
                     }
                 }
             }
-            FieldKind::Leaf { env, kind: LeafKind::Required { default, ty, .. }, ..} => {
-                let env = env_tokens(env);
-                let default_value = match default {
-                    Some(default) => {
-                        let meta = default_value_to_meta_expr(default, Some(&ty));
-                        quote! { std::option::Option::Some(#meta) }
-                    },
-                    None => quote! { std::option::Option::None },
+            FieldKind::Leaf { env, example, secret, kind, parse_env, deserialize_with, .. } => {
+                let env = opt_str_tokens(env);
+                let has_parse_env = parse_env.is_some();
+                let has_deserialize_with = deserialize_with.is_some();
+                let example = opt_expr_tokens(example.as_ref(), kind.inner_ty());
+                let kind = match kind {
+                    LeafKind::Optional { .. } => quote! { confique::meta::LeafKind::Optional },
+                    LeafKind::Required { default, ty } => {
+                        let default_value = opt_expr_tokens(default.as_ref(), ty);
+                        quote! {
+                            confique::meta::LeafKind::Required {
+                                default: #default_value,
+                            }
+                        }
+                    }
                 };
                 quote! {
                     confique::meta::FieldKind::Leaf {
                         env: #env,
-                        kind: confique::meta::LeafKind::Required {
-                            default: #default_value,
-                        },
+                        has_parse_env: #has_parse_env,
+                        has_deserialize_with: #has_deserialize_with,
+                        example: #example,
+                        secret: #secret,
+                        kind: #kind,
                     }
                 }
             }
@@ -59,16 +77,60 @@ pub(super) fn gen(input: &ir::Input) -> TokenStream {
             confique::meta::Field {
                 name: #name,
                 doc: &[ #(#doc),* ],
+                hidden_in_template: #hidden_in_template,
                 kind: #kind,
             }
         }
     });
 
+    // With `extend` fields, the fields of the extended types are spliced into
+    // the list of fields at compile time.
+    let fields = if input.fields.iter().any(|f| f.is_extend()) {
+        let mut meta_fields = meta_fields;
+        let mut lens = Vec::new();
+        let parts = input.fields.iter().map(|f| match &f.kind {
+            FieldKind::Nested { ty, extend: Some(overrides), .. } => {
+                let base = quote! { <#ty as confique::Config>::META.fields };
+                let overrides = overrides.iter().map(|(name, value)| {
+                    let name = name.unraw().to_string();
+                    let value = default_value_to_meta_expr(value, None);
+                    quote! { (#name, #value) }
+                });
+                lens.push(quote! { #base.len() });
+                quote! {
+                    &confique::internal::override_defaults::<{ #base.len() }>(
+                        #base,
+                        &[ #( #overrides ),* ],
+                    )
+                }
+            }
+            _ => {
+                lens.push(quote! { 1 });
+                let field = meta_fields.next().expect("bug: fewer meta fields than fields");
+                quote! { &[#field] }
+            }
+        }).collect::<Vec<_>>();
+        quote! {
+            &confique::internal::concat_fields::<{ 0 #( + #lens )* }>(&[ #( #parts ),* ])
+        }
+    } else {
+        quote! { &[ #( #meta_fields ),* ] }
+    };
+
+    let toml_preamble = opt_str_tokens(&input.preambles.toml);
+    let yaml_preamble = opt_str_tokens(&input.preambles.yaml);
+    let json5_preamble = opt_str_tokens(&input.preambles.json5);
+
     quote! {
         const META: confique::meta::Meta = confique::meta::Meta {
             name: #name_str,
             doc: &[ #(#doc),* ],
-            fields: &[ #( #meta_fields ),* ],
+            fields: #fields,
+            preambles: confique::meta::Preambles {
+                toml: #toml_preamble,
+                yaml: #yaml_preamble,
+                json5: #json5_preamble,
+            },
         };
     }
 }
@@ -99,7 +161,15 @@ macro_rules! match_literals {
 fn default_value_to_meta_expr(default: &Expr, ty: Option<&syn::Type>) -> TokenStream {
     match_literals!(default, ty, Expr, {
         Expr::Array(items) => {
-            let item_type = ty.and_then(get_array_item_type);
+            // Like in Rust, literals without suffix have the same type as
+            // their siblings with suffix.
+            let suffix_type = Expr::common_suffix(items).map(|suffix| {
+                syn::Type::from(syn::TypePath {
+                    qself: None,
+                    path: Ident::new(suffix, Span::call_site()).into(),
+                })
+            });
+            let item_type = suffix_type.as_ref().or_else(|| ty.and_then(get_array_item_type));
             let items = items.iter().map(|item| default_value_to_meta_expr(item, item_type));
             quote! { confique::meta::Expr::Array(&[#( #items ),*]) }
         }
@@ -116,6 +186,23 @@ fn default_value_to_meta_expr(default: &Expr, ty: Option<&syn::Type>) -> TokenSt
             });
             quote! { confique::meta::Expr::Map(&[#( #pairs ),*]) }
         }
+        Expr::Const(path) => {
+            // The type was checked while parsing. The constant is required to
+            // have exactly the field's type, which is used to pick the variant.
+            match ConstType::of(ty.expect("no field type for const default")) {
+                Some(ConstType::Int) => {
+                    let variant = infer_type("", ty, "I32", int_type_to_variant);
+                    quote! { confique::meta::Expr::Integer(confique::meta::Integer::#variant(#path)) }
+                }
+                Some(ConstType::Float) => {
+                    let variant = infer_type("", ty, "F64", float_type_to_variant);
+                    quote! { confique::meta::Expr::Float(confique::meta::Float::#variant(#path)) }
+                }
+                Some(ConstType::Bool) => quote! { confique::meta::Expr::Bool(#path) },
+                Some(ConstType::Str) => quote! { confique::meta::Expr::Str(#path) },
+                None => unreachable!("const default on field with unsupported type"),
+            }
+        }
     })
 }
 
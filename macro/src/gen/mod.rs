@@ -1,6 +1,7 @@
+use heck::ToShoutySnakeCase;
 use proc_macro2::{Span, TokenStream};
 use quote::{format_ident, quote, quote_spanned};
-use syn::{Ident, spanned::Spanned};
+use syn::{Ident, ext::IdentExt, spanned::Spanned};
 
 use crate::ir::{self, FieldKind, LeafKind};
 
@@ -26,9 +27,14 @@ fn gen_config_impl(input: &ir::Input) -> TokenStream {
     let field_names = input.fields.iter().map(|f| &f.name);
     let from_exprs = input.fields.iter().map(|f| {
         let field_name = &f.name;
-        let path = field_name.to_string();
+        let path = field_name.unraw().to_string();
         match f.kind {
-            FieldKind::Nested { .. } => {
+            // The fields are flattened into this struct, so their paths are
+            // not prefixed.
+            FieldKind::Nested { extend: Some(_), .. } => {
+                quote! { confique::Config::from_partial(partial.#field_name)? }
+            }
+            FieldKind::Nested { partial: None, .. } => {
                 quote! {
                     confique::internal::map_err_prefix_path(
                         confique::Config::from_partial(partial.#field_name),
@@ -36,6 +42,14 @@ fn gen_config_impl(input: &ir::Input) -> TokenStream {
                     )?
                 }
             }
+            FieldKind::Nested { partial: Some(_), .. } => {
+                quote! {
+                    confique::internal::map_err_prefix_path(
+                        confique::FieldPartial::into_value(partial.#field_name),
+                        #path,
+                    )?
+                }
+            }
             FieldKind::Leaf { kind: LeafKind::Optional { .. }, .. } => {
                 quote! { partial.#field_name }
             }
@@ -47,6 +61,60 @@ fn gen_config_impl(input: &ir::Input) -> TokenStream {
         }
     });
 
+    // Checks for `required_if` attributes. The expressions can access all
+    // fields by reference, like with the `validate(...)` attribute.
+    let required_if_checks = input.fields.iter().filter_map(|f| match &f.kind {
+        FieldKind::Leaf { kind: LeafKind::Optional { required_if: Some(expr), .. }, .. } => {
+            let field_name = &f.name;
+            let path = field_name.unraw().to_string();
+            Some(quote! {
+                if { #expr } {
+                    confique::internal::unwrap_or_missing_value_err(
+                        out.#field_name.as_ref(),
+                        #path,
+                    )?;
+                }
+            })
+        }
+        _ => None,
+    }).collect::<Vec<_>>();
+    let required_if = if required_if_checks.is_empty() {
+        quote! {}
+    } else {
+        let all_fields = input.fields.iter().map(|f| &f.name);
+        quote! {
+            {
+                #[allow(unused_variables)]
+                let Self { #( #all_fields ),* } = &out;
+                #( #required_if_checks )*
+            }
+        }
+    };
+
+    // Validators of nested fields, run on the built values.
+    let nested_validation = input.fields.iter().filter_map(|f| match &f.kind {
+        FieldKind::Nested { ty, validate: Some(validator), .. } => {
+            let field_name = &f.name;
+            let name = format!("{}::{}", input.name, field_name.unraw());
+            let validator = match validator {
+                ir::FieldValidator::Fn(f) => quote! { #f },
+                ir::FieldValidator::Simple(expr, msg) => quote! {
+                    |#field_name: &#ty| if { #expr } {
+                        std::result::Result::Ok(())
+                    } else {
+                        std::result::Result::Err(#msg)
+                    }
+                },
+            };
+            Some(quote! {
+                confique::internal::validate_struct(&out.#field_name, &#validator, #name)?;
+            })
+        }
+        _ => None,
+    });
+
+    let warnings = gen_warnings_fn(input);
+
     let validation = input.validate.as_ref().map(|v| {
         let struct_name = name.to_string();
         quote! {
@@ -54,25 +122,81 @@ fn gen_config_impl(input: &ir::Input) -> TokenStream {
         }
     });
 
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    let bounds = impl_bounds(input);
     let meta_item = meta::gen(input);
     quote! {
         #[automatically_derived]
-        impl confique::Config for #name {
-            type Partial = #partial_mod_name::#partial_struct_name;
+        impl #impl_generics confique::Config for #name #ty_generics where #( #bounds, )* {
+            type Partial = #partial_mod_name::#partial_struct_name #ty_generics;
 
             fn from_partial(partial: Self::Partial) -> std::result::Result<Self, confique::Error> {
                 let out = Self {
                     #( #field_names: #from_exprs, )*
                 };
+                #required_if
+                #( #nested_validation )*
                 #validation
                 std::result::Result::Ok(out)
             }
 
+            #warnings
+
             #meta_item
         }
     }
 }
 
+/// Generates the `Config::warnings` method, if there are `warn` attributes
+/// or nested fields that might have some. Like with `required_if`, the
+/// expressions can access all fields by reference.
+fn gen_warnings_fn(input: &ir::Input) -> Option<TokenStream> {
+    let nested = input.fields.iter()
+        .filter(|f| matches!(f.kind, FieldKind::Nested { partial: None, .. }))
+        .map(|f| {
+            let field_name = &f.name;
+            let path = field_name.unraw().to_string();
+            if f.is_extend() {
+                quote! {
+                    std::iter::Extend::extend(
+                        &mut out,
+                        confique::Config::warnings(&self.#field_name),
+                    );
+                }
+            } else {
+                quote! {
+                    std::iter::Extend::extend(&mut out, confique::internal::prefix_warnings(
+                        confique::Config::warnings(&self.#field_name),
+                        #path,
+                    ));
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+    if input.warnings.is_empty() && nested.is_empty() {
+        return None;
+    }
+
+    let checks = input.warnings.iter().map(|(expr, msg)| quote! {
+        if { #expr } {
+            out.push(confique::internal::warning(#msg));
+        }
+    });
+    let all_fields = input.fields.iter().map(|f| &f.name);
+    Some(quote! {
+        fn warnings(&self) -> std::vec::Vec<confique::Warning> {
+            let mut out = std::vec::Vec::new();
+            {
+                #[allow(unused_variables)]
+                let Self { #( #all_fields ),* } = self;
+                #( #checks )*
+            }
+            #( #nested )*
+            out
+        }
+    })
+}
+
 /// Generates the whole `mod ... { ... }` that defines the partial type and
 /// related items.
 fn gen_partial_mod(input: &ir::Input) -> TokenStream {
@@ -85,13 +209,18 @@ fn gen_partial_mod(input: &ir::Input) -> TokenStream {
     let Parts {
         field_names,
         struct_fields,
-        nested_bounds,
+        struct_bounds,
+        serde_bounds,
         empty_exprs,
         default_exprs,
         from_env_exprs,
         fallback_exprs,
         is_empty_exprs,
         is_complete_exprs,
+        semantic_eq_exprs,
+        semantic_eq_bounds,
+        extended_paths_exprs,
+        setters,
         extra_items,
     } = parts;
 
@@ -100,6 +229,27 @@ fn gen_partial_mod(input: &ir::Input) -> TokenStream {
     let visibility = &input.visibility;
     let partial_attrs = &input.partial_attrs;
     let struct_visibility = inner_visibility(&input.visibility, Span::call_site());
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    let where_preds = where_predicates(&input.generics);
+    let impl_bounds = impl_bounds(input);
+    let env_prefix = input.env_prefix.as_deref().unwrap_or("");
+
+    // For generic structs, we tell serde exactly which bounds are required
+    // instead of relying on its heuristic, which does not understand the
+    // nested `<T as Config>::Partial` types.
+    let serde_bound_attr = if is_generic(input) {
+        let bounds = serde_bounds.iter()
+            .map(|b| format!("{b}, "))
+            .collect::<String>();
+        quote! { #[serde(bound = #bounds)] }
+    } else {
+        quote! {}
+    };
+    let deny_unknown_fields_attr = if input.deny_unknown_fields {
+        quote! { #[serde(deny_unknown_fields)] }
+    } else {
+        quote! {}
+    };
     let module_doc = format!(
         "*Generated* by `confique`: helpers to implement `Config` for [`{}`].\n\
             \n\
@@ -118,13 +268,22 @@ fn gen_partial_mod(input: &ir::Input) -> TokenStream {
 
             #[derive(confique::serde::Deserialize)]
             #[serde(crate = "confique::serde")]
+            #serde_bound_attr
+            #deny_unknown_fields_attr
             #( #[ #partial_attrs ])*
-            #struct_visibility struct #struct_name {
+            #struct_visibility struct #struct_name #impl_generics
+            where
+                #( #where_preds, )*
+                #( #struct_bounds, )*
+            {
                 #( #struct_fields )*
             }
 
             #[automatically_derived]
-            impl confique::Partial for #struct_name where #( #nested_bounds, )* {
+            impl #impl_generics confique::Partial for #struct_name #ty_generics
+            where
+                #( #impl_bounds, )*
+            {
                 fn empty() -> Self {
                     Self {
                         #( #field_names: #empty_exprs, )*
@@ -138,6 +297,13 @@ fn gen_partial_mod(input: &ir::Input) -> TokenStream {
                 }
 
                 fn from_env() -> std::result::Result<Self, confique::Error> {
+                    confique::Partial::from_env_with_prefix(#env_prefix)
+                }
+
+                #[allow(unused_variables)]
+                fn from_env_with_prefix(
+                    env_prefix: &str,
+                ) -> std::result::Result<Self, confique::Error> {
                     std::result::Result::Ok(Self {
                         #( #field_names: #from_env_exprs, )*
                     })
@@ -156,6 +322,55 @@ fn gen_partial_mod(input: &ir::Input) -> TokenStream {
                 fn is_complete(&self) -> bool {
                     true #(&& #is_complete_exprs)*
                 }
+
+                #[allow(unused_variables)]
+                fn __extended_paths(
+                    prefix: &str,
+                    out: &mut std::vec::Vec<std::string::String>,
+                ) {
+                    #( #extended_paths_exprs )*
+                }
+            }
+
+            // The bounds are wrapped in `for<'__confique>` as otherwise bounds
+            // that don't hold would be an error instead of just making the
+            // impl not apply.
+            #[automatically_derived]
+            impl #impl_generics confique::internal::SemanticEq for #struct_name #ty_generics
+            where
+                #( #impl_bounds, )*
+                #( for<'__confique> #semantic_eq_bounds, )*
+            {
+                fn semantic_eq_with_defaults(&self, other: &Self, defaults: &Self) -> bool {
+                    true #(&& #semantic_eq_exprs)*
+                }
+            }
+
+            #[automatically_derived]
+            impl #impl_generics #struct_name #ty_generics
+            where
+                #( #impl_bounds, )*
+                #( for<'__confique> #semantic_eq_bounds, )*
+            {
+                /// Compares `self` and `other` while treating values equal to
+                /// their default as unset. See the `Config` derive docs.
+                #struct_visibility fn semantic_eq(&self, other: &Self) -> bool {
+                    confique::internal::SemanticEq::semantic_eq_with_defaults(
+                        self,
+                        other,
+                        &<Self as confique::Partial>::default_values(),
+                    )
+                }
+            }
+
+            // Chainable setters, mainly to conveniently build partials in code. They
+            // are prefixed with `set_` to not clash with the `Partial` methods.
+            #[automatically_derived]
+            impl #impl_generics #struct_name #ty_generics
+            where
+                #( #impl_bounds, )*
+            {
+                #( #setters )*
             }
 
             #extra_items
@@ -167,13 +382,18 @@ fn gen_partial_mod(input: &ir::Input) -> TokenStream {
 struct Parts {
     field_names: Vec<Ident>,
     struct_fields: Vec<TokenStream>,
-    nested_bounds: Vec<TokenStream>,
+    struct_bounds: Vec<TokenStream>,
+    serde_bounds: Vec<TokenStream>,
     empty_exprs: Vec<TokenStream>,
     default_exprs: Vec<TokenStream>,
     from_env_exprs: Vec<TokenStream>,
     fallback_exprs: Vec<TokenStream>,
     is_empty_exprs: Vec<TokenStream>,
     is_complete_exprs: Vec<TokenStream>,
+    semantic_eq_exprs: Vec<TokenStream>,
+    semantic_eq_bounds: Vec<TokenStream>,
+    extended_paths_exprs: Vec<TokenStream>,
+    setters: Vec<TokenStream>,
     extra_items: TokenStream,
 }
 
@@ -187,42 +407,151 @@ fn gen_parts_for_field(f: &ir::Field, input: &ir::Input, parts: &mut Parts) {
     // messages from the `derive(serde::Deserialize)` have the correct span.
     let field_visibility = inner_visibility(&input.visibility, field_name.span());
 
+    // Generic parameters and bounds for the helper functions we emit, which
+    // have to repeat all generic parameters of the struct.
+    let generic = is_generic(input);
+    let fn_params = fn_generic_params(&input.generics);
+    let where_preds = where_predicates(&input.generics);
+    let turbofish_args = generic_args(&input.generics);
 
     match &f.kind {
         // ----- Nested -------------------------------------------------------------
-        FieldKind::Nested { ty } => {
-            let ty_span = ty.span();
-            let field_ty = quote_spanned! {ty_span=> <#ty as confique::Config>::Partial };
+        FieldKind::Nested { ty, partial, env_prefix, extend, .. } => {
+            let (field_ty, bound) = match partial {
+                None => {
+                    let ty_span = ty.span();
+                    let field_ty = quote_spanned! {ty_span=> <#ty as confique::Config>::Partial };
+                    (field_ty, quote! { #ty: confique::Config })
+                }
+                Some(partial) => (quote! { #partial }, quote! { #partial: confique::Partial }),
+            };
+            let serde_attr = if extend.is_some() {
+                quote! { #[serde(flatten)] }
+            } else {
+                quote! { #[serde(default = "confique::Partial::empty")] }
+            };
             parts.struct_fields.push(quote! {
-                #[serde(default = "confique::Partial::empty")]
+                #serde_attr
                 #field_visibility #field_name: #field_ty,
             });
 
-            parts.nested_bounds.push(quote! { #ty: confique::Config });
+            if generic {
+                parts.struct_bounds.push(bound.clone());
+                parts.serde_bounds.push(bound);
+            }
             parts.empty_exprs.push(quote! { confique::Partial::empty() });
-            parts.default_exprs.push(quote! { confique::Partial::default_values() });
-            parts.from_env_exprs.push(quote! { confique::Partial::from_env()? });
+            parts.default_exprs.push(match extend {
+                Some(overrides) if !overrides.is_empty() => {
+                    let overrides = overrides.iter().map(|(name, value)| {
+                        let msg = format!("overridden default config value for \
+                            `{struct_name}::{}` cannot be deserialized", name.unraw());
+                        let value = default_value_to_deserializable_expr(value);
+                        quote! {
+                            confique::internal::override_default(
+                                &mut out.#name,
+                                confique::internal::into_deserializer(#value),
+                                #msg,
+                            );
+                        }
+                    });
+                    quote! {{
+                        let mut out: #field_ty = confique::Partial::default_values();
+                        #( #overrides )*
+                        out
+                    }}
+                }
+                _ => quote! { confique::Partial::default_values() },
+            });
+            let nested_prefix = match (env_prefix, extend) {
+                (Some(prefix), _) => prefix.clone(),
+                (None, Some(_)) => String::new(),
+                (None, None) => {
+                    format!("{}_", field_name.unraw().to_string().to_shouty_snake_case())
+                }
+            };
+            parts.from_env_exprs.push(quote! {
+                confique::Partial::from_env_with_prefix(
+                    &std::format!("{}{}", env_prefix, #nested_prefix),
+                )?
+            });
             parts.fallback_exprs.push(quote! {
                 self.#field_name.with_fallback(fallback.#field_name)
             });
+            let setter_doc = format!("Sets the nested partial `{}`.", field_name.unraw());
+            let setter_name = format_ident!("set_{}", field_name.unraw());
+            parts.setters.push(quote! {
+                #[doc = #setter_doc]
+                #[must_use]
+                #field_visibility fn #setter_name(mut self, value: #field_ty) -> Self {
+                    self.#field_name = value;
+                    self
+                }
+            });
             parts.is_empty_exprs.push(quote! { self.#field_name.is_empty() });
             parts.is_complete_exprs.push(quote! { self.#field_name.is_complete() });
+            parts.extended_paths_exprs.push(if extend.is_some() {
+                // All fields of the extended type end up in this struct.
+                quote! {
+                    for field in <#ty as confique::Config>::META.fields {
+                        out.push(confique::internal::join_path(prefix, field.name));
+                    }
+                }
+            } else {
+                let name = field_name.unraw().to_string();
+                quote! {
+                    <#field_ty as confique::Partial>::__extended_paths(
+                        &confique::internal::join_path(prefix, #name),
+                        out,
+                    );
+                }
+            });
+            parts.semantic_eq_bounds.push(quote! {
+                #field_ty: confique::internal::SemanticEq
+            });
+            parts.semantic_eq_exprs.push(quote! {
+                confique::internal::SemanticEq::semantic_eq_with_defaults(
+                    &self.#field_name,
+                    &other.#field_name,
+                    &defaults.#field_name,
+                )
+            });
         },
 
 
         // ----- Leaf ---------------------------------------------------------------
-        FieldKind::Leaf { kind, deserialize_with, validate, env, parse_env } => {
+        FieldKind::Leaf {
+            kind, deserialize_with, validate, env, env_auto, env_bool_lenient, parse_env,
+            flatten_serde, ..
+        } => {
             let inner_ty = kind.inner_ty();
 
             // This has an ugly name to avoid clashing with imported names.
-            let validate_fn_name = quote::format_ident!("__confique_validate_{field_name}");
+            let validate_fn_name = quote::format_ident!("__confique_validate_{}", field_name.unraw());
             let deserialize_fn_name
-                = quote::format_ident!("__confique_deserialize_direct_{field_name}");
+                = quote::format_ident!("__confique_deserialize_direct_{}", field_name.unraw());
 
             let default_deserialize_path = quote! {
                 <#inner_ty as confique::serde::Deserialize>::deserialize
             };
 
+            // For generic structs, the field type might depend on the generic
+            // parameters and thus we need to add bounds.
+            let deserialize_bound = if generic && deserialize_with.is_none() {
+                parts.serde_bounds.push(quote! { #inner_ty: confique::serde::Deserialize<'de> });
+                quote! { #inner_ty: confique::serde::Deserialize<'de>, }
+            } else {
+                quote! {}
+            };
+
+            // Our helper functions have to be called with explicit generic
+            // arguments, as not all of those necessarily appear in the
+            // signature.
+            let (fn_turbofish, deser_fn_turbofish) = if generic {
+                (quote! { ::<#( #turbofish_args ),*> }, quote! { ::<#( #turbofish_args, )* _> })
+            } else {
+                (quote! {}, quote! {})
+            };
+
             // We sometimes emit extra helper functions to avoid code duplication.
             // Validation should be part of the serialization. `validation_fn` is
             // `Some(Ident)` if there is a validator function. `deserialize_fn` is
@@ -234,9 +563,9 @@ fn gen_parts_for_field(f: &ir::Field, input: &ir::Input, parts: &mut Parts) {
                         confique::internal::validate_field(v, &#f)
                     },
                     ir::FieldValidator::Simple(expr, msg) => quote! {
-                        fn is_valid(#field_name: &#inner_ty) -> bool {
+                        let is_valid = |#field_name: &#inner_ty| -> bool {
                             #expr
-                        }
+                        };
                         confique::internal::validate_field(v, &|v| {
                             if !is_valid(v) {
                                 Err(#msg)
@@ -253,26 +582,34 @@ fn gen_parts_for_field(f: &ir::Field, input: &ir::Input, parts: &mut Parts) {
 
                 parts.extra_items.extend(quote! {
                     #[inline(never)]
-                    fn #validate_fn_name(
+                    fn #validate_fn_name<#fn_params>(
                         v: &#inner_ty,
-                    ) -> std::result::Result<(), confique::Error> {
+                    ) -> std::result::Result<(), confique::Error>
+                    where
+                        #( #where_preds, )*
+                    {
                         #validate_inner
                     }
 
-                    fn #deserialize_fn_name<'de, D>(
+                    fn #deserialize_fn_name<'de, #fn_params D>(
                         deserializer: D,
                     ) -> std::result::Result<#inner_ty, D::Error>
                     where
                         D: confique::serde::Deserializer<'de>,
+                        #( #where_preds, )*
+                        #deserialize_bound
                     {
                         let out = #deser_fn(deserializer)?;
-                        #validate_fn_name(&out)
+                        #validate_fn_name #fn_turbofish (&out)
                             .map_err(<D::Error as confique::serde::de::Error>::custom)?;
                         std::result::Result::Ok(out)
                     }
                 });
 
-                (Some(validate_fn_name), quote! { #deserialize_fn_name })
+                (
+                    Some(quote! { #validate_fn_name #fn_turbofish }),
+                    quote! { #deserialize_fn_name #deser_fn_turbofish },
+                )
             } else {
                 // If there is no validation, we will not create a custom
                 // deserialization function for this, so we either use `T::deserialize`
@@ -288,23 +625,34 @@ fn gen_parts_for_field(f: &ir::Field, input: &ir::Input, parts: &mut Parts) {
             parts.struct_fields.push({
                 // If there is a custom deserializer or a validator, we need to
                 // set the serde `deserialize_with` attribute.
-                let attr = if deserialize_with.is_some() || validate.is_some() {
+                let attr = if *flatten_serde {
+                    // This function returns `None` if none of the keys of the
+                    // type are present, instead of an error.
+                    quote! {
+                        #[serde(
+                            flatten,
+                            deserialize_with = "confique::internal::deserialize_flattened",
+                        )]
+                    }
+                } else if deserialize_with.is_some() || validate.is_some() {
                     // Since the struct field is `Option<T>`, we need to create
                     // another wrapper deserialization function, that always
                     // returns `Some`.
-                    let fn_name = quote::format_ident!("__confique_deserialize_some_{field_name}");
+                    let fn_name = quote::format_ident!("__confique_deserialize_some_{}", field_name.unraw());
                     parts.extra_items.extend(quote! {
-                        fn #fn_name<'de, D>(
+                        fn #fn_name<'de, #fn_params D>(
                             deserializer: D,
                         ) -> std::result::Result<std::option::Option<#inner_ty>, D::Error>
                         where
                             D: confique::serde::Deserializer<'de>,
+                            #( #where_preds, )*
+                            #deserialize_bound
                         {
                             #deserialize_fn(deserializer).map(std::option::Option::Some)
                         }
                     });
 
-                    let attr_value = fn_name.to_string();
+                    let attr_value = format!("{fn_name}{deser_fn_turbofish}");
                     quote! {
                         #[serde(default, deserialize_with = #attr_value)]
                     }
@@ -323,16 +671,45 @@ fn gen_parts_for_field(f: &ir::Field, input: &ir::Input, parts: &mut Parts) {
             parts.empty_exprs.push(quote! { std::option::Option::None });
             parts.fallback_exprs.push(quote! { self.#field_name.or(fallback.#field_name) });
             parts.is_empty_exprs.push(quote! { self.#field_name.is_none() });
+            let setter_doc = format!("Sets `{}` to the given value.", field_name.unraw());
+            let setter_name = format_ident!("set_{}", field_name.unraw());
+            parts.setters.push(quote! {
+                #[doc = #setter_doc]
+                #[must_use]
+                #field_visibility fn #setter_name(
+                    mut self,
+                    value: impl std::convert::Into<#inner_ty>,
+                ) -> Self {
+                    self.#field_name = std::option::Option::Some(std::convert::Into::into(value));
+                    self
+                }
+            });
             if kind.is_required() {
                 parts.is_complete_exprs.push(quote! { self.#field_name.is_some() });
             }
+            parts.semantic_eq_bounds.push(quote! { #inner_ty: std::cmp::PartialEq });
+            parts.semantic_eq_exprs.push(quote! {
+                confique::internal::leaf_semantic_eq(
+                    &self.#field_name,
+                    &other.#field_name,
+                    &defaults.#field_name,
+                )
+            });
 
             // Code for `Partial::default_values()`
             parts.default_exprs.push(match kind {
+                // Use the value deserialized from no keys at all, if that
+                // works, e.g. due to `#[serde(default)]`.
+                LeafKind::Required { .. } if *flatten_serde => {
+                    quote! { confique::internal::flattened_default() }
+                }
                 LeafKind::Required { default: Some(default), .. } => {
                     let msg = format!("default config value for `{qualified_name}` \
                         cannot be deserialized");
-                    let expr = default_value_to_deserializable_expr(&default);
+                    let expr = match default {
+                        ir::Expr::Const(path) => const_path_in_partial_mod(path, input),
+                        _ => default_value_to_deserializable_expr(&default),
+                    };
                     quote! {
                         std::option::Option::Some(
                             #deserialize_fn(confique::internal::into_deserializer(#expr))
@@ -343,13 +720,20 @@ fn gen_parts_for_field(f: &ir::Field, input: &ir::Input, parts: &mut Parts) {
                 _ => quote! { std::option::Option::None },
             });
 
-            // Code for `Partial::from_env()`
-            parts.from_env_exprs.push(match (env, parse_env) {
-                (None, _) => quote! { std::option::Option::None },
-                (Some(key), None) => quote! {
+            // Code for `Partial::from_env()`. The explicit key is tried first,
+            // then the one derived from the field's path.
+            let auto_key = field_name.unraw().to_string().to_shouty_snake_case();
+            let keys = env.iter().map(|key| quote! { #key })
+                .chain(env_auto.then(|| quote! { &std::format!("{}{}", env_prefix, #auto_key) }));
+            let load_exprs = keys.map(|key| match parse_env {
+                None if *env_bool_lenient => quote! {
+                    confique::internal::from_env_bool_lenient(
+                        #key, #qualified_name, #deserialize_fn)?
+                },
+                None => quote! {
                     confique::internal::from_env(#key, #qualified_name, #deserialize_fn)?
                 },
-                (Some(key), Some(parse_env)) => {
+                Some(parse_env) => {
                     let validator = match &validate_fn {
                         Some(f) => quote! { #f },
                         None => quote! { |_| std::result::Result::<(), String>::Ok(()) },
@@ -359,11 +743,93 @@ fn gen_parts_for_field(f: &ir::Field, input: &ir::Input, parts: &mut Parts) {
                             #key, #qualified_name, #parse_env, #validator)?
                     }
                 }
+            }).collect::<Vec<_>>();
+            let mut load_exprs = load_exprs.into_iter().rev();
+            parts.from_env_exprs.push(match load_exprs.next() {
+                None => quote! { std::option::Option::None },
+                Some(last) => load_exprs.fold(last, |fallback, load| quote! {
+                    match #load {
+                        std::option::Option::Some(v) => std::option::Option::Some(v),
+                        std::option::Option::None => #fallback,
+                    }
+                }),
             });
         }
     }
 }
 
+/// Returns `true` if the struct has any generic parameters.
+fn is_generic(input: &ir::Input) -> bool {
+    !input.generics.params.is_empty()
+}
+
+/// Returns the predicates of the struct's where clause (if any).
+fn where_predicates(generics: &syn::Generics) -> Vec<&syn::WherePredicate> {
+    generics.where_clause.iter().flat_map(|w| &w.predicates).collect()
+}
+
+/// Returns the where predicates for the `Config` and `Partial` impls: the ones
+/// from the struct definition plus the bounds required by the fields. For
+/// non-generic structs, bounds on leaf fields are not necessary, as the field
+/// types are checked concretely anyway.
+fn impl_bounds(input: &ir::Input) -> Vec<TokenStream> {
+    let mut out = where_predicates(&input.generics)
+        .into_iter()
+        .map(|p| quote! { #p })
+        .collect::<Vec<_>>();
+
+    for f in &input.fields {
+        match &f.kind {
+            FieldKind::Nested { ty, partial: None, .. } => out.push(quote! { #ty: confique::Config }),
+            FieldKind::Nested { ty, partial: Some(partial), .. } => {
+                out.push(quote! { #partial: confique::FieldPartial<Value = #ty> });
+            }
+            FieldKind::Leaf { kind, deserialize_with: None, .. } if is_generic(input) => {
+                let ty = kind.inner_ty();
+                out.push(quote! { #ty: confique::serde::de::DeserializeOwned });
+            }
+            FieldKind::Leaf { .. } => {}
+        }
+    }
+
+    out
+}
+
+/// Returns the generic parameters of the struct (with bounds, without
+/// defaults) to be used in the parameter list of helper functions. Each
+/// parameter is followed by a comma.
+fn fn_generic_params(generics: &syn::Generics) -> TokenStream {
+    let params = generics.params.iter().map(|p| {
+        let mut p = p.clone();
+        match &mut p {
+            syn::GenericParam::Type(t) => {
+                t.eq_token = None;
+                t.default = None;
+            }
+            syn::GenericParam::Const(c) => {
+                c.eq_token = None;
+                c.default = None;
+            }
+            syn::GenericParam::Lifetime(_) => {}
+        }
+        p
+    });
+    quote! { #( #params, )* }
+}
+
+/// Returns the generic type and const arguments of the struct to be used in
+/// the turbofish when calling helper functions. Lifetimes are omitted, as
+/// those are inferred.
+fn generic_args(generics: &syn::Generics) -> Vec<&Ident> {
+    generics.params.iter()
+        .filter_map(|p| match p {
+            syn::GenericParam::Type(t) => Some(&t.ident),
+            syn::GenericParam::Const(c) => Some(&c.ident),
+            syn::GenericParam::Lifetime(_) => None,
+        })
+        .collect()
+}
+
 /// Returns the names of the module and struct for the partial type:
 /// `(mod_name, struct_name)`.
 fn partial_names(original_name: &Ident) -> (Ident, Ident) {
@@ -382,6 +848,7 @@ fn default_value_to_deserializable_expr(expr: &ir::Expr) -> TokenStream {
         ir::Expr::Int(lit) => quote! { #lit },
         ir::Expr::Float(lit) => quote! { #lit },
         ir::Expr::Bool(lit) => quote! { #lit },
+        ir::Expr::Const(path) => quote! { #path },
         ir::Expr::Array(arr) => {
             let items = arr.iter().map(default_value_to_deserializable_expr);
 
@@ -415,6 +882,23 @@ fn default_value_to_deserializable_expr(expr: &ir::Expr) -> TokenStream {
     }
 }
 
+/// Returns the path of a `default = const PATH` constant to be used inside
+/// the generated module: `Self` has to refer to the config type instead of
+/// the partial type and relative paths have to start one module further up.
+fn const_path_in_partial_mod(path: &syn::Path, input: &ir::Input) -> TokenStream {
+    let name = &input.name;
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+    let first = path.segments.first().map(|s| s.ident.to_string());
+    let rest = path.segments.iter().skip(1);
+    match first.as_deref() {
+        _ if path.leading_colon.is_some() => quote! { #path },
+        Some("Self") => quote! { <super::#name #ty_generics> #( :: #rest )* },
+        Some("self") => quote! { super #( :: #rest )* },
+        Some("super") => quote! { super::#path },
+        _ => quote! { #path },
+    }
+}
+
 /// Returns tokens defining the visibility of the items in the inner module.
 fn inner_visibility(outer: &syn::Visibility, span: Span) -> TokenStream {
     match outer {
@@ -9,33 +9,89 @@ pub(crate) struct Input {
     pub(crate) visibility: syn::Visibility,
     pub(crate) partial_attrs: Vec<TokenStream>,
     pub(crate) validate: Option<syn::Path>,
+    pub(crate) env_prefix: Option<String>,
+    pub(crate) deny_unknown_fields: bool,
+
+    /// Expressions and messages of `#[config(warn(...))]` attributes.
+    pub(crate) warnings: Vec<(TokenStream, String)>,
+
+    /// Format-specific template preambles, e.g. `#[config(toml_preamble = "...")]`.
+    pub(crate) preambles: Preambles,
     pub(crate) name: syn::Ident,
+    pub(crate) generics: syn::Generics,
     pub(crate) fields: Vec<Field>,
 }
 
+#[derive(Default)]
+pub(crate) struct Preambles {
+    pub(crate) toml: Option<String>,
+    pub(crate) yaml: Option<String>,
+    pub(crate) json5: Option<String>,
+}
+
 pub(crate) struct Field {
     pub(crate) doc: Vec<String>,
     pub(crate) name: syn::Ident,
+    pub(crate) no_template: bool,
     pub(crate) kind: FieldKind,
 
     // TODO:
     // - serde attributes
     // - attributes
-    // - example
+}
+
+impl Field {
+    /// Whether this field has the `#[config(extend)]` attribute.
+    pub(crate) fn is_extend(&self) -> bool {
+        matches!(self.kind, FieldKind::Nested { extend: Some(_), .. })
+    }
 }
 
 pub(crate) enum FieldKind {
     Leaf {
         env: Option<String>,
+
+        /// Example value, only used for `META` (see `#[config(example = ...)]`).
+        example: Option<Expr>,
+
+        /// Whether the value is redacted in logs (see `#[config(secret)]`).
+        secret: bool,
+
+        /// Whether to also load this field from the env key derived from its
+        /// path (see `#[config(env_auto)]`).
+        env_auto: bool,
+
+        /// Whether unrecognized bool values in env vars are treated as unset
+        /// (see `#[config(env_bool_lenient)]`).
+        env_bool_lenient: bool,
         deserialize_with: Option<syn::Path>,
         parse_env: Option<syn::Path>,
         validate: Option<FieldValidator>,
+
+        /// Whether the field is flattened into the parent via
+        /// `#[serde(flatten)]` (see `#[config(flatten_serde)]`).
+        flatten_serde: bool,
         kind: LeafKind,
     },
 
     /// A nested configuration. The type is never `Option<_>`.
     Nested {
         ty: syn::Type,
+
+        /// Partial type specified via `with_partial`. If set, `ty` does not
+        /// need to implement `Config`.
+        partial: Option<syn::Type>,
+
+        /// Env prefix segment replacing the one derived from the field name
+        /// (see `#[config(env_prefix = "...")]` on nested fields).
+        env_prefix: Option<String>,
+
+        /// Set if the fields of `ty` are flattened into this struct via
+        /// `#[config(extend)]`. Contains the overridden default values.
+        extend: Option<Vec<(syn::Ident, Expr)>>,
+
+        /// Validator run on the built value in the parent's `from_partial`.
+        validate: Option<FieldValidator>,
     },
 }
 
@@ -54,6 +110,9 @@ pub(crate) enum LeafKind {
     /// A leaf with type `Option<_>`.
     Optional {
         inner_ty: syn::Type,
+
+        /// Expression of the `required_if` attribute.
+        required_if: Option<TokenStream>,
     },
 }
 
@@ -65,7 +124,7 @@ impl LeafKind {
     pub(crate) fn inner_ty(&self) -> &syn::Type {
         match self {
             Self::Required { ty, .. } => ty,
-            Self::Optional { inner_ty } => inner_ty,
+            Self::Optional { inner_ty, .. } => inner_ty,
         }
     }
 }
@@ -79,6 +138,41 @@ pub(crate) enum Expr {
     Bool(syn::LitBool),
     Array(Vec<Expr>),
     Map(Vec<MapEntry>),
+
+    /// `const PATH`, referring to a constant of a primitive type. Only allowed
+    /// as top-level default value.
+    Const(syn::Path),
+}
+
+impl Expr {
+    /// Returns the type suffix if this is an integer or float literal.
+    pub(crate) fn numeric_suffix(&self) -> Option<&str> {
+        match self {
+            Self::Int(i) => Some(i.suffix()),
+            Self::Float(f) => Some(f.suffix()),
+            _ => None,
+        }
+    }
+
+    /// Returns the type suffix of the numeric literals in the array `items`
+    /// if any of them has one. For collection types, suffixes are checked to
+    /// be consistent while parsing.
+    pub(crate) fn common_suffix(items: &[Self]) -> Option<&str> {
+        items.iter()
+            .filter_map(Self::numeric_suffix)
+            .find(|suffix| !suffix.is_empty())
+    }
+
+    pub(crate) fn span(&self) -> proc_macro2::Span {
+        match self {
+            Self::Str(l) => l.span(),
+            Self::Int(l) => l.span(),
+            Self::Float(l) => l.span(),
+            Self::Bool(l) => l.span(),
+            Self::Const(p) => syn::spanned::Spanned::span(p),
+            Self::Array(_) | Self::Map(_) => proc_macro2::Span::call_site(),
+        }
+    }
 }
 
 pub(crate) struct MapEntry {
@@ -94,6 +188,48 @@ pub(crate) enum MapKey {
     Bool(syn::LitBool),
 }
 
+/// The field types for which `default = const PATH` is supported, as we need
+/// to know the variant of `meta::Expr` to store the constant in.
+#[derive(Clone, Copy)]
+pub(crate) enum ConstType {
+    Int,
+    Float,
+    Bool,
+    Str,
+}
+
+impl ConstType {
+    pub(crate) fn of(ty: &syn::Type) -> Option<Self> {
+        match ty {
+            syn::Type::Path(syn::TypePath { qself: None, path }) => {
+                let last = path.segments.last()?;
+                if !last.arguments.is_empty() {
+                    return None;
+                }
+                match &*last.ident.to_string() {
+                    "String" => Some(Self::Str),
+
+                    // Primitives have to be written as single identifier, as
+                    // that's what the type inference for meta values expects.
+                    _ if path.get_ident().is_none() => None,
+                    "u8" | "u16" | "u32" | "u64" | "u128" | "usize"
+                        | "i8" | "i16" | "i32" | "i64" | "i128" | "isize" => Some(Self::Int),
+                    "f32" | "f64" => Some(Self::Float),
+                    "bool" => Some(Self::Bool),
+                    _ => None,
+                }
+            }
+            syn::Type::Reference(r) if r.mutability.is_none() => match &*r.elem {
+                syn::Type::Path(p) if p.path.is_ident("str") => Some(Self::Str),
+                _ => None,
+            },
+            syn::Type::Group(g) => Self::of(&g.elem),
+            syn::Type::Paren(p) => Self::of(&p.elem),
+            _ => None,
+        }
+    }
+}
+
 impl From<MapKey> for Expr {
     fn from(src: MapKey) -> Self {
         match src {
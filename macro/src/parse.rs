@@ -2,8 +2,14 @@ use proc_macro2::{Delimiter, Group, Ident, TokenStream, TokenTree};
 use syn::{Error, Token, parse::{Parse, ParseStream}, spanned::Spanned, punctuated::Punctuated};
 
 use crate::{
-    ir::{Expr, Field, FieldKind, FieldValidator, Input, LeafKind, MapEntry, MapKey},
-    util::{is_option, unwrap_option},
+    ir::{
+        ConstType, Expr, Field, FieldKind, FieldValidator, Input, LeafKind, MapEntry, MapKey,
+        Preambles,
+    },
+    util::{
+        collection_item_type, find_borrowing_lifetime, is_option, map_entry_types,
+        unwrap_option,
+    },
 };
 
 
@@ -23,13 +29,38 @@ impl Input {
             .map(Field::from_ast)
             .collect::<Result<Vec<_>, _>>()?;
 
+        if let Some(f) = fields.iter().find(|f| f.is_extend()) {
+            if !input.generics.params.is_empty() {
+                return Err(Error::new(f.name.span(), "`extend` fields are not supported \
+                    in generic structs"));
+            }
+            if attrs.deny_unknown_fields {
+                return Err(Error::new(f.name.span(), "cannot use `extend` fields in \
+                    structs with the `deny_unknown_fields` attribute"));
+            }
+        }
+
+        if attrs.deny_unknown_fields {
+            let flattened = fields.iter().find(|f| {
+                matches!(f.kind, FieldKind::Leaf { flatten_serde: true, .. })
+            });
+            if let Some(f) = flattened {
+                return Err(Error::new(f.name.span(), "cannot use `flatten_serde` fields in \
+                    structs with the `deny_unknown_fields` attribute"));
+            }
+        }
 
         Ok(Self {
             doc,
             visibility: input.vis,
             partial_attrs: attrs.partial_attrs,
             validate: attrs.validate,
+            env_prefix: attrs.env_prefix,
+            deny_unknown_fields: attrs.deny_unknown_fields,
+            warnings: attrs.warnings,
+            preambles: attrs.preambles,
             name: input.ident,
+            generics: input.generics,
             fields,
         })
     }
@@ -41,11 +72,21 @@ impl Input {
 struct StructAttrs {
     partial_attrs: Vec<TokenStream>,
     validate: Option<syn::Path>,
+    env_prefix: Option<String>,
+    deny_unknown_fields: bool,
+    warnings: Vec<(TokenStream, String)>,
+    preambles: Preambles,
 }
 
 enum StructAttr {
     PartialAttrs(TokenStream),
     Validate(syn::Path),
+    EnvPrefix(String),
+    DenyUnknownFields,
+    Warn(TokenStream, String),
+    TomlPreamble(String),
+    YamlPreamble(String),
+    Json5Preamble(String),
 }
 
 impl StructAttrs {
@@ -75,6 +116,27 @@ impl StructAttrs {
                         duplicate_if!(out.validate.is_some());
                         out.validate = Some(path);
                     }
+                    StructAttr::EnvPrefix(prefix) => {
+                        duplicate_if!(out.env_prefix.is_some());
+                        out.env_prefix = Some(prefix);
+                    }
+                    StructAttr::DenyUnknownFields => {
+                        duplicate_if!(out.deny_unknown_fields);
+                        out.deny_unknown_fields = true;
+                    }
+                    StructAttr::Warn(expr, msg) => out.warnings.push((expr, msg)),
+                    StructAttr::TomlPreamble(s) => {
+                        duplicate_if!(out.preambles.toml.is_some());
+                        out.preambles.toml = Some(s);
+                    }
+                    StructAttr::YamlPreamble(s) => {
+                        duplicate_if!(out.preambles.yaml.is_some());
+                        out.preambles.yaml = Some(s);
+                    }
+                    StructAttr::Json5Preamble(s) => {
+                        duplicate_if!(out.preambles.json5.is_some());
+                        out.preambles.json5 = Some(s);
+                    }
                 }
             }
         }
@@ -88,6 +150,12 @@ impl StructAttr {
         match self {
             Self::PartialAttrs(_) => "partial_attr",
             Self::Validate(_) => "validate",
+            Self::EnvPrefix(_) => "env_prefix",
+            Self::DenyUnknownFields => "deny_unknown_fields",
+            Self::Warn(..) => "warn",
+            Self::TomlPreamble(_) => "toml_preamble",
+            Self::YamlPreamble(_) => "yaml_preamble",
+            Self::Json5Preamble(_) => "json5_preamble",
         }
     }
 }
@@ -106,12 +174,59 @@ impl Parse for StructAttr {
                 Ok(Self::PartialAttrs(g.stream()))
             }
             "validate" => parse_eq_value(input).map(Self::Validate),
+            "env_prefix" => parse_env_key(input).map(Self::EnvPrefix),
+            "deny_unknown_fields" => {
+                assert_empty_or_comma(input)?;
+                Ok(Self::DenyUnknownFields)
+            }
+            "warn" => {
+                let (expr, msg) = parse_expr_and_msg(input)?;
+                assert_empty_or_comma(input)?;
+                Ok(Self::Warn(expr, msg))
+            }
+            "toml_preamble" => parse_eq_value(input)
+                .map(|s: syn::LitStr| Self::TomlPreamble(s.value())),
+            "yaml_preamble" => parse_eq_value(input)
+                .map(|s: syn::LitStr| Self::YamlPreamble(s.value())),
+            "json5_preamble" => parse_eq_value(input)
+                .map(|s: syn::LitStr| Self::Json5Preamble(s.value())),
             _ => Err(syn::Error::new(ident.span(), "unknown confique attribute")),
         }
     }
 }
 
 
+/// Parses `(<expr>, "msg")` as used by `validate` and `warn`.
+fn parse_expr_and_msg(input: ParseStream) -> Result<(TokenStream, String), Error> {
+    let g: Group = input.parse()?;
+
+    // Instead of properly parsing an expression, which would
+    // require the `full` feature of syn, increasing compile
+    // time, we just validate the last two/three tokens and
+    // just assume the tokens before are a valid expression.
+    let mut tokens = g.stream().into_iter().collect::<Vec<_>>();
+    if tokens.len() < 3 {
+        return Err(syn::Error::new(
+            g.span(),
+            "expected at least three tokens, found fewer",
+        ));
+    }
+
+    // Ignore trailing comma
+    if is_comma(tokens.last().unwrap()) {
+        let _ = tokens.pop();
+    }
+
+    let msg = as_string_lit(tokens.pop().unwrap())?;
+    let sep_comma = tokens.pop().unwrap();
+    if !is_comma(&sep_comma) {
+        return Err(syn::Error::new(sep_comma.span(), "expected comma"));
+    }
+
+    Ok((tokens.into_iter().collect(), msg))
+}
+
+
 // ===== Struct fields =============================================================
 
 impl Field {
@@ -122,48 +237,190 @@ impl Field {
         let err = |msg| Err(Error::new(field.ident.span(), msg));
 
         // TODO: check no other attributes are here
-        let kind = if attrs.nested {
+        let kind = if let Some(overrides) = attrs.extend {
             if is_option(&field.ty) {
-                return err("nested configurations cannot be optional (type `Option<_>`)");
+                return err("fields with `extend` attribute cannot be optional \
+                    (type `Option<_>`)");
             }
 
             let conflicting_attrs = [
+                ("nested", attrs.nested),
+                ("with_partial", attrs.with_partial.is_some()),
+                ("no_template", attrs.no_template),
                 ("default", attrs.default.is_some()),
+                ("example", attrs.example.is_some()),
+                ("secret", attrs.secret),
                 ("env", attrs.env.is_some()),
+                ("env_auto", attrs.env_auto),
+                ("env_bool_lenient", attrs.env_bool_lenient),
+                ("parse_env", attrs.parse_env.is_some()),
                 ("deserialize_with", attrs.deserialize_with.is_some()),
                 ("validate", attrs.validate.is_some()),
+                ("required_if", attrs.required_if.is_some()),
+                ("flatten_serde", attrs.flatten_serde),
+            ];
+            for (conflicting, is_set) in conflicting_attrs {
+                if is_set {
+                    return Err(Error::new(
+                        field.ident.span(),
+                        format!("cannot specify `extend` and `{conflicting}` \
+                            attributes at the same time"),
+                    ));
+                }
+            }
+
+            FieldKind::Nested {
+                ty: field.ty,
+                partial: None,
+                env_prefix: attrs.env_prefix,
+                extend: Some(overrides),
+                validate: None,
+            }
+        } else if attrs.nested || attrs.with_partial.is_some() {
+            let keyword = if attrs.nested { "nested" } else { "with_partial" };
+            if attrs.nested && attrs.with_partial.is_some() {
+                return err("cannot specify `nested` and `with_partial` attributes \
+                    at the same time");
+            }
+            if is_option(&field.ty) {
+                return if attrs.nested {
+                    err("nested configurations cannot be optional (type `Option<_>`)")
+                } else {
+                    err("fields with `with_partial` attribute cannot be optional \
+                        (type `Option<_>`)")
+                };
+            }
+
+            let conflicting_attrs = [
+                ("default", attrs.default.is_some()),
+                ("example", attrs.example.is_some()),
+                ("secret", attrs.secret),
+                ("env", attrs.env.is_some()),
+                ("env_auto", attrs.env_auto),
+                ("env_bool_lenient", attrs.env_bool_lenient),
+                ("parse_env", attrs.parse_env.is_some()),
+                ("deserialize_with", attrs.deserialize_with.is_some()),
+                ("required_if", attrs.required_if.is_some()),
+                ("flatten_serde", attrs.flatten_serde),
             ];
 
-            for (keyword, is_set) in conflicting_attrs {
+            for (conflicting, is_set) in conflicting_attrs {
                 if is_set {
                     return Err(Error::new(
                         field.ident.span(),
-                        format!("cannot specify `nested` and `{keyword}` \
+                        format!("cannot specify `{keyword}` and `{conflicting}` \
                             attributes at the same time")
                     ));
                 }
             }
 
-            FieldKind::Nested { ty: field.ty }
+            FieldKind::Nested {
+                ty: field.ty,
+                partial: attrs.with_partial,
+                env_prefix: attrs.env_prefix,
+                extend: None,
+                validate: attrs.validate,
+            }
         } else {
-            if attrs.env.is_none() && attrs.parse_env.is_some() {
-                return err("cannot specify `parse_env` attribute without the `env` attribute");
+            if attrs.env_prefix.is_some() {
+                return err("`env_prefix` can only be specified on nested fields \
+                    (`nested` or `with_partial`)");
+            }
+
+            if attrs.flatten_serde {
+                let conflicting_attrs = [
+                    ("default", attrs.default.is_some()),
+                    ("example", attrs.example.is_some()),
+                    ("env", attrs.env.is_some()),
+                    ("env_auto", attrs.env_auto),
+                    ("parse_env", attrs.parse_env.is_some()),
+                    ("deserialize_with", attrs.deserialize_with.is_some()),
+                    ("validate", attrs.validate.is_some()),
+                ];
+
+                for (conflicting, is_set) in conflicting_attrs {
+                    if is_set {
+                        return Err(Error::new(
+                            field.ident.span(),
+                            format!("cannot specify `flatten_serde` and `{conflicting}` \
+                                attributes at the same time")
+                        ));
+                    }
+                }
+            }
+
+            if attrs.env.is_none() && !attrs.env_auto && attrs.parse_env.is_some() {
+                return err("cannot specify `parse_env` attribute without the `env` or \
+                    `env_auto` attribute");
+            }
+
+            if attrs.env_bool_lenient {
+                if attrs.env.is_none() && !attrs.env_auto {
+                    return err("cannot specify `env_bool_lenient` attribute without the \
+                        `env` or `env_auto` attribute");
+                }
+                if attrs.parse_env.is_some() {
+                    return err("cannot specify `env_bool_lenient` and `parse_env` \
+                        attributes at the same time");
+                }
+                let inner_ty = unwrap_option(&field.ty).unwrap_or(&field.ty);
+                if !matches!(inner_ty, syn::Type::Path(p) if p.path.is_ident("bool")) {
+                    return err("`env_bool_lenient` can only be specified on fields of \
+                        type `bool` or `Option<bool>`");
+                }
+            }
+
+            // Partial types have to be `for<'de> Deserialize<'de>`, so borrowed
+            // data is not possible. Without this check, the user would get a
+            // fairly confusing error from serde.
+            if let Some(span) = find_borrowing_lifetime(&field.ty) {
+                return Err(Error::new(span, "leaf fields cannot borrow data (i.e. have \
+                    a type with a lifetime other than `'static`), as configuration is \
+                    deserialized into owned values. Use an owned type instead, e.g. \
+                    `String` instead of `&'a str`."));
+            }
+
+            let inner_ty = unwrap_option(&field.ty).unwrap_or(&field.ty);
+            for expr in attrs.default.iter().chain(&attrs.example) {
+                check_default_suffixes(expr, inner_ty)?;
             }
 
             let kind = match unwrap_option(&field.ty) {
                 Some(_) if attrs.default.is_some() => {
                     return err("optional fields (type `Option<_>`) cannot have default \
-                            values (`#[config(default = ...)]`)");
+                            values (`#[config(default = ...)]`), as the field would never \
+                            be `None`. Use the inner type instead.");
+                },
+                Some(inner) => LeafKind::Optional {
+                    inner_ty: inner.clone(),
+                    required_if: attrs.required_if,
                 },
-                Some(inner) => LeafKind::Optional { inner_ty: inner.clone() },
-                None => LeafKind::Required { default: attrs.default, ty: field.ty },
+                None if attrs.required_if.is_some() => {
+                    return err("`required_if` can only be specified on optional fields \
+                        (type `Option<_>`) as other fields are always required");
+                }
+                None => {
+                    if let Some(Expr::Const(path)) = &attrs.default {
+                        if ConstType::of(&field.ty).is_none() {
+                            return Err(Error::new_spanned(path, "`default = const ...` is \
+                                only supported for fields of integer, float, `bool`, \
+                                `String` or `&str` type. Use a literal instead."));
+                        }
+                    }
+                    LeafKind::Required { default: attrs.default, ty: field.ty }
+                }
             };
 
             FieldKind::Leaf {
                 env: attrs.env,
+                example: attrs.example,
+                secret: attrs.secret,
+                env_auto: attrs.env_auto,
+                env_bool_lenient: attrs.env_bool_lenient,
                 deserialize_with: attrs.deserialize_with,
                 parse_env: attrs.parse_env,
                 validate: attrs.validate,
+                flatten_serde: attrs.flatten_serde,
                 kind,
             }
         };
@@ -171,6 +428,9 @@ impl Field {
         Ok(Self {
             doc,
             name: field.ident.expect("bug: expected named field"),
+            // Flattened fields are not represented in templates, as we don't
+            // know the fields of the external type.
+            no_template: attrs.no_template || attrs.flatten_serde,
             kind,
         })
     }
@@ -182,20 +442,40 @@ impl Field {
 #[derive(Default)]
 struct FieldAttrs {
     nested: bool,
+    no_template: bool,
     default: Option<Expr>,
+    example: Option<Expr>,
+    secret: bool,
     env: Option<String>,
+    env_auto: bool,
+    env_bool_lenient: bool,
+    env_prefix: Option<String>,
+    extend: Option<Vec<(syn::Ident, Expr)>>,
     deserialize_with: Option<syn::Path>,
     parse_env: Option<syn::Path>,
     validate: Option<FieldValidator>,
+    required_if: Option<TokenStream>,
+    with_partial: Option<syn::Type>,
+    flatten_serde: bool,
 }
 
 enum FieldAttr {
     Nested,
+    NoTemplate,
     Default(Expr),
+    Example(Expr),
+    Secret,
     Env(String),
+    EnvAuto,
+    EnvBoolLenient,
+    EnvPrefix(String),
+    Extend(Vec<(syn::Ident, Expr)>),
     DeserializeWith(syn::Path),
     ParseEnv(syn::Path),
     Validate(FieldValidator),
+    RequiredIf(TokenStream),
+    WithPartial(syn::Type),
+    FlattenSerde,
 }
 
 impl FieldAttrs {
@@ -224,14 +504,42 @@ impl FieldAttrs {
                         duplicate_if!(out.default.is_some());
                         out.default = Some(expr);
                     }
+                    FieldAttr::Example(expr) => {
+                        duplicate_if!(out.example.is_some());
+                        out.example = Some(expr);
+                    }
+                    FieldAttr::Secret => {
+                        duplicate_if!(out.secret);
+                        out.secret = true;
+                    }
                     FieldAttr::Nested => {
                         duplicate_if!(out.nested);
                         out.nested = true;
                     }
+                    FieldAttr::NoTemplate => {
+                        duplicate_if!(out.no_template);
+                        out.no_template = true;
+                    }
                     FieldAttr::Env(key) => {
                         duplicate_if!(out.env.is_some());
                         out.env = Some(key);
                     }
+                    FieldAttr::EnvAuto => {
+                        duplicate_if!(out.env_auto);
+                        out.env_auto = true;
+                    }
+                    FieldAttr::EnvBoolLenient => {
+                        duplicate_if!(out.env_bool_lenient);
+                        out.env_bool_lenient = true;
+                    }
+                    FieldAttr::EnvPrefix(prefix) => {
+                        duplicate_if!(out.env_prefix.is_some());
+                        out.env_prefix = Some(prefix);
+                    }
+                    FieldAttr::Extend(overrides) => {
+                        duplicate_if!(out.extend.is_some());
+                        out.extend = Some(overrides);
+                    }
                     FieldAttr::ParseEnv(path) => {
                         duplicate_if!(out.parse_env.is_some());
                         out.parse_env = Some(path);
@@ -244,6 +552,18 @@ impl FieldAttrs {
                         duplicate_if!(out.validate.is_some());
                         out.validate = Some(path);
                     }
+                    FieldAttr::RequiredIf(expr) => {
+                        duplicate_if!(out.required_if.is_some());
+                        out.required_if = Some(expr);
+                    }
+                    FieldAttr::WithPartial(ty) => {
+                        duplicate_if!(out.with_partial.is_some());
+                        out.with_partial = Some(ty);
+                    }
+                    FieldAttr::FlattenSerde => {
+                        duplicate_if!(out.flatten_serde);
+                        out.flatten_serde = true;
+                    }
                 }
             }
         }
@@ -256,11 +576,21 @@ impl FieldAttr {
     fn keyword(&self) -> &'static str {
         match self {
             Self::Nested => "nested",
+            Self::NoTemplate => "no_template",
             Self::Default(_) => "default",
+            Self::Example(_) => "example",
+            Self::Secret => "secret",
             Self::Env(_) => "env",
+            Self::EnvAuto => "env_auto",
+            Self::EnvBoolLenient => "env_bool_lenient",
+            Self::EnvPrefix(_) => "env_prefix",
+            Self::Extend(_) => "extend",
             Self::ParseEnv(_) => "parse_env",
             Self::DeserializeWith(_) => "deserialize_with",
             Self::Validate(_) => "validate",
+            Self::RequiredIf(_) => "required_if",
+            Self::WithPartial(_) => "with_partial",
+            Self::FlattenSerde => "flatten_serde",
         }
     }
 }
@@ -274,53 +604,72 @@ impl Parse for FieldAttr {
                 Ok(Self::Nested)
             }
 
-            "default" => parse_eq_value(input).map(Self::Default),
+            "no_template" => {
+                assert_empty_or_comma(input)?;
+                Ok(Self::NoTemplate)
+            }
 
-            "env" => {
-                let key: syn::LitStr = parse_eq_value(input)?;
-                let value = key.value();
-                if value.contains('=') || value.contains('\0') {
-                    return Err(syn::Error::new(
-                        key.span(),
-                        "environment variable key must not contain '=' or null bytes",
-                    ));
+            "default" => {
+                if input.peek(Token![=]) && input.peek2(Token![const]) {
+                    let _: Token![=] = input.parse()?;
+                    let _: Token![const] = input.parse()?;
+                    let path: syn::Path = input.parse()?;
+                    assert_empty_or_comma(input)?;
+                    Ok(Self::Default(Expr::Const(path)))
+                } else {
+                    parse_eq_value(input).map(Self::Default)
                 }
+            }
+
+            "example" => parse_eq_value(input).map(Self::Example),
+            "secret" => {
+                assert_empty_or_comma(input)?;
+                Ok(Self::Secret)
+            }
 
-                Ok(Self::Env(value))
+            "env" => parse_env_key(input).map(Self::Env),
+            "env_prefix" => parse_env_key(input).map(Self::EnvPrefix),
+            "extend" => {
+                // Optional list of overridden defaults: `extend(port = 8080)`.
+                let mut overrides = Vec::new();
+                if input.peek(syn::token::Paren) {
+                    let content;
+                    syn::parenthesized!(content in input);
+                    let list = Punctuated::<_, Token![,]>::parse_terminated_with(&content, |i| {
+                        let name: syn::Ident = i.parse()?;
+                        let _: Token![=] = i.parse()?;
+                        let value: Expr = i.parse()?;
+                        Ok((name, value))
+                    })?;
+                    overrides.extend(list);
+                }
+                assert_empty_or_comma(input)?;
+                Ok(Self::Extend(overrides))
+            }
+
+            "env_auto" => {
+                assert_empty_or_comma(input)?;
+                Ok(Self::EnvAuto)
+            }
+
+            "env_bool_lenient" => {
+                assert_empty_or_comma(input)?;
+                Ok(Self::EnvBoolLenient)
             }
 
             "parse_env" => parse_eq_value(input).map(Self::ParseEnv),
             "deserialize_with" => parse_eq_value(input).map(Self::DeserializeWith),
+            "with_partial" => parse_eq_value(input).map(Self::WithPartial),
+            "flatten_serde" => {
+                assert_empty_or_comma(input)?;
+                Ok(Self::FlattenSerde)
+            }
             "validate" => {
                 if input.peek(Token![=]) {
                     parse_eq_value(input).map(|path| Self::Validate(FieldValidator::Fn(path)))
                 } else if input.peek(syn::token::Paren) {
-                    let g: Group = input.parse()?;
-
-                    // Instead of properly parsing an expression, which would
-                    // require the `full` feature of syn, increasing compile
-                    // time, we just validate the last two/three tokens and
-                    // just assume the tokens before are a valid expression.
-                    let mut tokens = g.stream().into_iter().collect::<Vec<_>>();
-                    if tokens.len() < 3 {
-                        return Err(syn::Error::new(
-                            g.span(),
-                            "expected at least three tokens, found fewer",
-                        ));
-                    }
-
-                    // Ignore trailing comma
-                    if is_comma(tokens.last().unwrap()) {
-                        let _ = tokens.pop();
-                    }
-
-                    let msg = as_string_lit(tokens.pop().unwrap())?;
-                    let sep_comma = tokens.pop().unwrap();
-                    if !is_comma(&sep_comma) {
-                        return Err(syn::Error::new(sep_comma.span(), "expected comma"));
-                    }
-
-                    Ok(Self::Validate(FieldValidator::Simple(tokens.into_iter().collect(), msg)))
+                    let (expr, msg) = parse_expr_and_msg(input)?;
+                    Ok(Self::Validate(FieldValidator::Simple(expr, msg)))
                 } else {
                     Err(syn::Error::new(
                         ident.span(),
@@ -330,6 +679,17 @@ impl Parse for FieldAttr {
                 }
             }
 
+            "required_if" => {
+                // Like with `validate`, we don't parse the expression but
+                // just pass it through.
+                let g: Group = input.parse()?;
+                if g.delimiter() != Delimiter::Parenthesis || g.stream().is_empty() {
+                    return Err(Error::new_spanned(g, "expected `required_if(<expr>)`"));
+                }
+                assert_empty_or_comma(input)?;
+                Ok(Self::RequiredIf(g.stream()))
+            }
+
             _ => Err(syn::Error::new(ident.span(), "unknown confique attribute")),
         }
     }
@@ -341,7 +701,7 @@ impl Parse for FieldAttr {
 impl Parse for Expr {
     fn parse(input: ParseStream) -> Result<Self, syn::Error> {
         let msg = "invalid default value. Allowed are only: certain literals \
-            (string, integer, float, bool), and arrays";
+            (string, integer, float, bool), arrays, maps and `const PATH`";
 
         if input.peek(syn::token::Bracket) {
             // ----- Array -----
@@ -369,6 +729,66 @@ impl Parse for Expr {
     }
 }
 
+/// Makes sure that all integer and float literals among the elements of an
+/// array or map default value (and of nested arrays and maps) have the same
+/// type suffix or none at all, if `ty` is a homogeneous collection like
+/// `Vec<_>` or `HashMap<_, _>`. Otherwise, we would generate meta values with
+/// different types and very confusing errors in the generated code. Other
+/// types are not checked.
+fn check_default_suffixes(default: &Expr, ty: &syn::Type) -> Result<(), Error> {
+    check_same_type(&[default], ty)
+}
+
+/// Checks the suffixes of `exprs`, which all have the type `ty`.
+fn check_same_type(exprs: &[&Expr], ty: &syn::Type) -> Result<(), Error> {
+    // Elements of nested collections all have the same type, too.
+    fn flatten<'e, 't>(
+        exprs: impl IntoIterator<Item = &'e Expr>,
+        ty: &'t syn::Type,
+        out: &mut Vec<(&'e Expr, &'t syn::Type)>,
+    ) {
+        for expr in exprs {
+            match (expr, collection_item_type(ty)) {
+                (Expr::Array(items), Some(item_ty)) => flatten(items, item_ty, out),
+                _ => out.push((expr, ty)),
+            }
+        }
+    }
+
+    let mut elements = Vec::new();
+    flatten(exprs.iter().copied(), ty, &mut elements);
+    check_consistent_suffixes(elements.iter().map(|(expr, _)| *expr))?;
+    for (expr, ty) in elements {
+        if let (Expr::Map(entries), Some((key_ty, value_ty))) = (expr, map_entry_types(ty)) {
+            let keys = entries.iter().map(|e| e.key.clone().into()).collect::<Vec<Expr>>();
+            let values = entries.iter().map(|e| &e.value).collect::<Vec<_>>();
+            check_same_type(&keys.iter().collect::<Vec<_>>(), key_ty)?;
+            check_same_type(&values, value_ty)?;
+        }
+    }
+    Ok(())
+}
+
+/// Checks that all integer and float literals among `items` have the same type
+/// suffix or none at all.
+fn check_consistent_suffixes<'a>(items: impl IntoIterator<Item = &'a Expr>) -> Result<(), Error> {
+    let mut expected = None;
+    for (item, suffix) in items.into_iter().filter_map(|i| Some((i, i.numeric_suffix()?))) {
+        match expected {
+            _ if suffix.is_empty() => {}
+            None => expected = Some(suffix),
+            Some(expected) if expected == suffix => {}
+            Some(expected) => return Err(Error::new(
+                item.span(),
+                format!("conflicting type suffixes `{expected}` and `{suffix}` in default \
+                    value: all elements of an array (and all keys/values of a map) need to \
+                    have the same type, so use the same suffix for all or for none of them"),
+            )),
+        }
+    }
+    Ok(())
+}
+
 impl Parse for MapEntry {
     fn parse(input: ParseStream) -> Result<Self, syn::Error> {
         let key: MapKey = input.parse()?;
@@ -429,6 +849,21 @@ fn parse_eq_value<T: syn::parse::Parse>(input: ParseStream) -> Result<T, Error>
     Ok(out)
 }
 
+/// Parses `= "..."` with a string that is valid as (part of) an environment
+/// variable key.
+fn parse_env_key(input: ParseStream) -> Result<String, Error> {
+    let key: syn::LitStr = parse_eq_value(input)?;
+    let value = key.value();
+    if value.contains('=') || value.contains('\0') {
+        return Err(syn::Error::new(
+            key.span(),
+            "environment variable key must not contain '=' or null bytes",
+        ));
+    }
+
+    Ok(value)
+}
+
 /// Extracts all doc string attributes from the list and returns them as list of
 /// strings (in order).
 fn extract_doc(attrs: &mut Vec<syn::Attribute>) -> Vec<String> {
@@ -1,3 +1,7 @@
+use proc_macro2::{Span, TokenStream, TokenTree};
+use quote::ToTokens;
+
+
 
 /// Checks if the given type is an `Option` and if so, return the inner type.
 ///
@@ -40,7 +44,84 @@ pub(crate) fn unwrap_option(ty: &syn::Type) -> Option<&syn::Type> {
     }
 }
 
+/// Returns the element type if the given type looks like a homogeneous
+/// sequence, i.e. an array, slice or one of the sequence types of `std` (like
+/// `Vec<_>`). Like `unwrap_option`, this only checks what the type looks like.
+pub(crate) fn collection_item_type(ty: &syn::Type) -> Option<&syn::Type> {
+    const SEQUENCES: &[&str] =
+        &["Vec", "VecDeque", "LinkedList", "BinaryHeap", "HashSet", "BTreeSet"];
+
+    match ty {
+        syn::Type::Slice(slice) => Some(&slice.elem),
+        syn::Type::Array(array) => Some(&array.elem),
+        _ => generic_args(ty, SEQUENCES).and_then(|args| args.first().copied()),
+    }
+}
+
+/// Returns the key and value types if the given type looks like one of the
+/// map types of `std` (`HashMap` or `BTreeMap`).
+pub(crate) fn map_entry_types(ty: &syn::Type) -> Option<(&syn::Type, &syn::Type)> {
+    match generic_args(ty, &["HashMap", "BTreeMap"])?.as_slice() {
+        [key, value, ..] => Some((key, value)),
+        _ => None,
+    }
+}
+
+/// Returns the generic type arguments of `ty` if the last segment of its path
+/// is one of `names`.
+fn generic_args<'a>(ty: &'a syn::Type, names: &[&str]) -> Option<Vec<&'a syn::Type>> {
+    let last = match ty {
+        syn::Type::Path(p) => p.path.segments.last()?,
+        _ => return None,
+    };
+    if !names.iter().any(|name| last.ident == name) {
+        return None;
+    }
+
+    match &last.arguments {
+        syn::PathArguments::AngleBracketed(args) => Some(args.args.iter()
+            .filter_map(|arg| match arg {
+                syn::GenericArgument::Type(t) => Some(t),
+                _ => None,
+            })
+            .collect()),
+        _ => None,
+    }
+}
+
 /// Returns `true` if the given type is `Option<_>`.
 pub(crate) fn is_option(ty: &syn::Type) -> bool {
     unwrap_option(ty).is_some()
 }
+
+/// Returns the span of the first lifetime other than `'static` in the given
+/// type, e.g. `'a` in `&'a str` or `Cow<'a, str>`.
+///
+/// Like `unwrap_option`, this only checks what the type looks like. It works
+/// on the token level to also find lifetimes inside arbitrarily nested types.
+pub(crate) fn find_borrowing_lifetime(ty: &syn::Type) -> Option<Span> {
+    fn find(tokens: TokenStream) -> Option<Span> {
+        let mut it = tokens.into_iter().peekable();
+        while let Some(tt) = it.next() {
+            match tt {
+                TokenTree::Punct(p) if p.as_char() == '\'' => {
+                    if let Some(TokenTree::Ident(ident)) = it.peek() {
+                        if ident != "static" {
+                            return Some(p.span().join(ident.span()).unwrap_or(ident.span()));
+                        }
+                    }
+                }
+                TokenTree::Group(g) => {
+                    if let Some(span) = find(g.stream()) {
+                        return Some(span);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        None
+    }
+
+    find(ty.to_token_stream())
+}
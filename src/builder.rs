@@ -1,10 +1,9 @@
-#[cfg(any(feature = "toml", feature = "yaml", feature = "json5"))]
-use std::path::PathBuf;
+use std::{path::PathBuf, sync::mpsc, thread, time::Duration};
 
-use crate::{Config, Error, Partial};
+use crate::{error::ErrorInner, Config, Error, Partial, Warning};
 
 #[cfg(any(feature = "toml", feature = "yaml", feature = "json5"))]
-use crate::File;
+use crate::{File, FileFormat};
 
 
 
@@ -15,11 +14,25 @@ use crate::File;
 /// [`Config::builder`].
 pub struct Builder<C: Config> {
     sources: Vec<Source<C>>,
+    helpful_errors: bool,
+    stop_when_complete: bool,
+    #[cfg(any(feature = "toml", feature = "yaml", feature = "json5"))]
+    require_known_keys: bool,
+    #[cfg(any(feature = "toml", feature = "yaml", feature = "json5"))]
+    base_dir: Option<PathBuf>,
 }
 
 impl<C: Config> Builder<C> {
     pub(crate) fn new() -> Self {
-        Self { sources: vec![] }
+        Self {
+            sources: vec![],
+            helpful_errors: false,
+            stop_when_complete: false,
+            #[cfg(any(feature = "toml", feature = "yaml", feature = "json5"))]
+            require_known_keys: false,
+            #[cfg(any(feature = "toml", feature = "yaml", feature = "json5"))]
+            base_dir: None,
+        }
     }
 
     /// Adds a configuration file as source. Infers the format from the file
@@ -28,9 +41,152 @@ impl<C: Config> Builder<C> {
     ///
     /// The file is not considered required: if the file does not exist, an
     /// empty configuration (`C::Partial::empty()`) is used for this layer.
+    ///
+    /// Relative paths are resolved against the directory set via
+    /// [`Builder::base_dir`] (if any) or the current working directory.
     #[cfg(any(feature = "toml", feature = "yaml", feature = "json5"))]
     pub fn file(mut self, path: impl Into<PathBuf>) -> Self {
-        self.sources.push(Source::File(path.into()));
+        let path = match &self.base_dir {
+            Some(base) => base.join(path.into()),
+            None => path.into(),
+        };
+        self.sources.push(Source::File(path));
+        self
+    }
+
+    /// Resolves relative paths of all files added via [`Builder::file`]
+    /// *after* this call against `dir` instead of the current working
+    /// directory, e.g. the directory of the executable or
+    /// `$XDG_CONFIG_HOME/app`. Absolute paths are not affected. Calling this
+    /// again replaces the base directory for files added afterwards.
+    ///
+    /// ```
+    /// use confique::Config;
+    ///
+    /// #[derive(Config)]
+    /// struct Conf {
+    ///     #[config(default = 8080)]
+    ///     port: u16,
+    /// }
+    ///
+    /// let conf = Conf::builder()
+    ///     .file("/etc/app/config.toml")
+    ///     .base_dir("/opt/app")
+    ///     .file("config.toml") // -> /opt/app/config.toml
+    ///     .load();
+    /// ```
+    #[cfg(any(feature = "toml", feature = "yaml", feature = "json5"))]
+    pub fn base_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.base_dir = Some(dir.into());
+        self
+    }
+
+    /// Adds configuration embedded in the binary as source, usually via
+    /// `include_str!`. This is useful for single-binary distributions that
+    /// ship default configuration which is more complex than what
+    /// `#[config(default = ...)]` supports, e.g. a default list of
+    /// plugins per platform.
+    ///
+    /// As sources specified earlier have a higher priority, you usually want
+    /// to add this last so that it is overridden by files and env variables.
+    /// Values not specified in `content` are still filled from the
+    /// `#[config(default = ...)]` values.
+    ///
+    /// ```
+    /// use confique::{Config, FileFormat};
+    ///
+    /// #[derive(Config)]
+    /// struct Conf {
+    ///     #[config(default = 8080)]
+    ///     port: u16,
+    ///     plugins: Vec<String>,
+    /// }
+    ///
+    /// // Usually `include_str!("defaults.toml")`.
+    /// const EMBEDDED: &str = "plugins = ['auth', 'metrics']";
+    ///
+    /// # #[cfg(feature = "toml")]
+    /// # {
+    /// let conf = Conf::builder()
+    ///     .env()
+    ///     .file("app.toml")
+    ///     .embedded(EMBEDDED, FileFormat::Toml)
+    ///     .load()?;
+    ///
+    /// assert_eq!(conf.port, 8080);
+    /// assert_eq!(conf.plugins, ["auth", "metrics"]);
+    /// # }
+    /// # Ok::<_, confique::Error>(())
+    /// ```
+    #[cfg(any(feature = "toml", feature = "yaml", feature = "json5"))]
+    pub fn embedded(mut self, content: &'static str, format: FileFormat) -> Self {
+        self.sources.push(Source::Embedded(content, format));
+        self
+    }
+
+    /// Makes [`Builder::load`] fail if any file added via [`Builder::file`]
+    /// contains values, but none that are known to the configuration. See
+    /// [`File::require_known_keys`] for more information. This is a guard
+    /// against misconfigured paths, e.g. a `--config` argument pointing to
+    /// an unrelated file.
+    #[cfg(any(feature = "toml", feature = "yaml", feature = "json5"))]
+    pub fn require_known_keys(mut self) -> Self {
+        self.require_known_keys = true;
+        self
+    }
+
+    /// Makes errors about missing required values more helpful for end users
+    /// by including the environment variable the value can be loaded from (if
+    /// any) and the field's doc comment, e.g.:
+    ///
+    /// ```text
+    /// required configuration value is missing: 'http.port'; set it in a
+    /// configuration file or via environment variable `PORT`; The port to
+    /// listen on.
+    /// ```
+    pub fn helpful_errors(mut self) -> Self {
+        self.helpful_errors = true;
+        self
+    }
+
+    /// Makes [`Builder::load`] stop loading sources as soon as all required
+    /// values are set (see [`Partial::is_complete`]). The remaining sources
+    /// are not loaded at all, e.g. their files are not read. This is useful
+    /// if a high-priority source is supposed to fully specify the
+    /// configuration, e.g. a file passed via `--config-only`.
+    ///
+    /// Note the subtle semantics:
+    /// - Optional values (`Option<_>` fields) are not required for
+    ///   completeness. So if the loaded sources don't set them, they are
+    ///   `None`, even if a skipped source would have set them.
+    /// - Default values (`#[config(default = ...)]`) are not considered when
+    ///   checking completeness, as they are only merged after all sources.
+    ///   So fields with defaults have to be set by the loaded sources to stop
+    ///   early.
+    ///
+    /// ```
+    /// use confique::{Config, Partial};
+    ///
+    /// #[derive(Config)]
+    /// struct Conf {
+    ///     port: u16,
+    ///     name: Option<String>,
+    /// }
+    ///
+    /// type PartialConf = <Conf as Config>::Partial;
+    ///
+    /// let conf = Conf::builder()
+    ///     .stop_when_complete()
+    ///     .preloaded(PartialConf { port: Some(80), name: None })
+    ///     .preloaded(PartialConf { port: Some(8080), name: Some("peter".into()) })
+    ///     .load()?;
+    ///
+    /// assert_eq!(conf.port, 80);
+    /// assert_eq!(conf.name, None);
+    /// # Ok::<_, confique::Error>(())
+    /// ```
+    pub fn stop_when_complete(mut self) -> Self {
+        self.stop_when_complete = true;
         self
     }
 
@@ -40,12 +196,261 @@ impl<C: Config> Builder<C> {
         self
     }
 
+    /// Adds a directory as source in which each file contains a single value,
+    /// like Kubernetes mounts secrets and config maps. The file for a field is
+    /// named like its path (see [`Builder::lookup_fn`]), with the `.`
+    /// separators either kept or replaced by directories. For example, the
+    /// field `database.password` is loaded from `<dir>/database/password`
+    /// or, if that doesn't exist, `<dir>/database.password`.
+    ///
+    /// The file contents are deserialized like environment variables (see
+    /// [`env`][crate::env]), after removing a single trailing newline. Fields
+    /// without a file are not set. Other files in the directory are ignored,
+    /// and if the directory does not exist, this source is simply empty.
+    /// Files are only looked up by the paths of fields, never by traversing
+    /// the directory, so symlinks are followed like for any file read. A file
+    /// that cannot be read (e.g. a symlink loop) results in an IO error
+    /// mentioning its path.
+    ///
+    /// ```no_run
+    /// use confique::Config;
+    ///
+    /// #[derive(Config)]
+    /// struct Conf {
+    ///     #[config(nested)]
+    ///     database: DbConf,
+    /// }
+    ///
+    /// #[derive(Config)]
+    /// struct DbConf {
+    ///     password: String,
+    /// }
+    ///
+    /// # fn main() -> Result<(), confique::Error> {
+    /// // Loads `/run/secrets/database/password` or `/run/secrets/database.password`.
+    /// let conf = Conf::builder()
+    ///     .dir_as_keys("/run/secrets")
+    ///     .load()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn dir_as_keys(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.sources.push(Source::DirAsKeys(dir.into()));
+        self
+    }
+
+    /// Adds a function as source that is called with the path of each leaf
+    /// field and returns the value for that field, or `None` if it has none.
+    ///
+    /// Paths consist of the field names, joined by `.`, e.g. `http.port` for
+    /// the field `port` inside the nested field `http`. The returned strings
+    /// are deserialized like environment variables (see [`env`][crate::env]),
+    /// i.e. they are parsed into numbers, booleans and so on as required by
+    /// the field type. Note however that `parse_env` functions are not used.
+    ///
+    /// This is a simple way to plug in custom sources of individual values,
+    /// e.g. secrets stored in the OS keyring:
+    ///
+    /// ```
+    /// use confique::Config;
+    ///
+    /// #[derive(Config)]
+    /// struct Conf {
+    ///     #[config(nested)]
+    ///     db: DbConf,
+    /// }
+    ///
+    /// #[derive(Config)]
+    /// struct DbConf {
+    ///     #[config(default = "peter")]
+    ///     user: String,
+    ///     password: String,
+    /// }
+    ///
+    /// # fn keyring_get(_: &str) -> Option<String> { Some("secret".into()) }
+    /// # fn main() -> Result<(), confique::Error> {
+    /// let conf = Conf::builder()
+    ///     .lookup_fn(|path| match path {
+    ///         "db.password" => keyring_get("myapp-db-password"),
+    ///         _ => None,
+    ///     })
+    ///     .load()?;
+    ///
+    /// assert_eq!(conf.db.password, "secret");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn lookup_fn(
+        mut self,
+        f: impl FnMut(&str) -> Option<String> + Send + 'static,
+    ) -> Self {
+        self.sources.push(Source::Lookup(Box::new(f)));
+        self
+    }
+
+    /// Adds a list of overrides in the form `path=value` as source, e.g. as
+    /// passed via `--set` command line arguments. This is a lightweight
+    /// alternative to defining command line arguments for all values.
+    ///
+    /// Paths are like in [`Builder::lookup_fn`], i.e. field names joined by
+    /// `.`, and have to refer to a leaf field. Everything after the first `=`
+    /// is the value, which is deserialized like an environment variable (see
+    /// [`env`][crate::env]), e.g. `http.port=9090` or `log.stdout=false`. If
+    /// a path is given multiple times, the last value is used.
+    ///
+    /// [`Builder::load`] returns an error if an override does not contain
+    /// `=`, if its path does not refer to a leaf field, or if its value
+    /// cannot be deserialized into the field's type.
+    ///
+    /// ```
+    /// use confique::Config;
+    ///
+    /// #[derive(Config)]
+    /// struct Conf {
+    ///     #[config(nested)]
+    ///     http: HttpConf,
+    /// }
+    ///
+    /// #[derive(Config)]
+    /// struct HttpConf {
+    ///     #[config(default = 8080)]
+    ///     port: u16,
+    /// }
+    ///
+    /// # fn main() -> Result<(), confique::Error> {
+    /// let conf = Conf::builder()
+    ///     .overrides(["http.port=9090"])
+    ///     .load()?;
+    ///
+    /// assert_eq!(conf.http.port, 9090);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn overrides(mut self, overrides: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        let overrides = overrides.into_iter().map(Into::into).collect();
+        self.sources.push(Source::Overrides(overrides));
+        self
+    }
+
+    /// Adds a function as source that loads a partial configuration. This is
+    /// the escape hatch for custom sources not supported by confique itself,
+    /// e.g. fetching secrets from a vault or converting settings loaded by
+    /// another library. `load` is called once when [`Builder::load`] reaches
+    /// this source; if it returns an error, loading is aborted with that
+    /// error. Use [`Error::custom_source`] to create errors for failures of
+    /// your source.
+    ///
+    /// ```
+    /// use confique::Config;
+    ///
+    /// #[derive(Config)]
+    /// struct Conf {
+    ///     #[config(default = "localhost")]
+    ///     host: String,
+    ///     password: String,
+    /// }
+    ///
+    /// # fn vault_get(_: &str) -> String { "secret".into() }
+    /// # fn main() -> Result<(), confique::Error> {
+    /// type PartialConf = <Conf as Config>::Partial;
+    ///
+    /// let conf = Conf::builder()
+    ///     .env()
+    ///     .source_fn(|| Ok(PartialConf {
+    ///         host: None,
+    ///         password: Some(vault_get("app/password")),
+    ///     }))
+    ///     .load()?;
+    ///
+    /// assert_eq!(conf.password, "secret");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn source_fn(
+        mut self,
+        load: impl FnOnce() -> Result<C::Partial, Error> + Send + 'static,
+    ) -> Self {
+        self.sources.push(Source::Fn(Box::new(load)));
+        self
+    }
+
+    /// Like [`Builder::source_fn`], but aborts with an error if `load` does
+    /// not finish within `timeout`. This is mostly
+    /// useful for sources that might hang, e.g. ones fetching configuration
+    /// over the network. The built-in sources (files and environment
+    /// variables) don't need this.
+    ///
+    /// `load` is executed on a new thread when [`Builder::load`] reaches this
+    /// source. If the timeout is exceeded, loading fails with an error, but
+    /// the thread cannot be stopped and keeps running in the background until
+    /// `load` returns; its result is discarded then. If `load` panics, the
+    /// panic is propagated to the caller of `Builder::load`.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use confique::{Config, Partial};
+    ///
+    /// #[derive(Config)]
+    /// struct Conf {
+    ///     #[config(default = 8080)]
+    ///     port: u16,
+    /// }
+    ///
+    /// # fn fetch_remote_config() -> Result<<Conf as Config>::Partial, confique::Error> {
+    /// #     Ok(Partial::empty())
+    /// # }
+    /// # fn main() -> Result<(), confique::Error> {
+    /// let conf = Conf::builder()
+    ///     .source_with_timeout(fetch_remote_config, Duration::from_secs(5))
+    ///     .load()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn source_with_timeout(
+        mut self,
+        load: impl FnOnce() -> Result<C::Partial, Error> + Send + 'static,
+        timeout: Duration,
+    ) -> Self
+    where
+        C::Partial: Send + 'static,
+    {
+        self.sources.push(Source::Fn(Box::new(move || {
+            let (tx, rx) = mpsc::sync_channel(1);
+            let handle = thread::spawn(move || {
+                // If the receiver is gone, the timeout was exceeded and we
+                // can just discard the result.
+                let _ = tx.send(load());
+            });
+
+            match rx.recv_timeout(timeout) {
+                Ok(result) => result,
+                Err(mpsc::RecvTimeoutError::Timeout) => {
+                    Err(ErrorInner::SourceTimeout { timeout }.into())
+                }
+                // The sender was dropped without sending, i.e. `load` panicked.
+                Err(mpsc::RecvTimeoutError::Disconnected) => match handle.join() {
+                    Err(panic) => std::panic::resume_unwind(panic),
+                    Ok(()) => unreachable!("source thread finished without sending result"),
+                },
+            }
+        })));
+        self
+    }
+
     /// Adds an already loaded partial configuration as source.
     pub fn preloaded(mut self, partial: C::Partial) -> Self {
         self.sources.push(Source::Preloaded(partial));
         self
     }
 
+    /// Adds multiple already loaded partial configurations as sources. This
+    /// is equivalent to calling [`Builder::preloaded`] for each item in order,
+    /// meaning that the first item has the highest priority of those.
+    pub fn preloaded_many(mut self, partials: impl IntoIterator<Item = C::Partial>) -> Self {
+        self.sources.extend(partials.into_iter().map(Source::Preloaded));
+        self
+    }
+
     /// Loads all configured sources in order. Earlier sources have a higher
     /// priority, later sources only fill potential gaps.
     ///
@@ -56,21 +461,82 @@ impl<C: Config> Builder<C> {
         for source in self.sources {
             let layer = match source {
                 #[cfg(any(feature = "toml", feature = "yaml", feature = "json5"))]
-                Source::File(path) => File::new(path)?.load()?,
+                Source::File(path) => {
+                    let mut file = File::new(path)?;
+                    if self.require_known_keys {
+                        file = file.require_known_keys();
+                    }
+                    file.load()?
+                }
+                #[cfg(any(feature = "toml", feature = "yaml", feature = "json5"))]
+                Source::Embedded(content, format) => {
+                    format.parse(content.as_bytes(), || "embedded configuration".into())?
+                }
                 Source::Env => C::Partial::from_env()?,
+                Source::Lookup(mut f) => {
+                    crate::values::from_lookup(&C::META, &mut f, "lookup function")?
+                }
+                Source::DirAsKeys(dir) => crate::values::from_dir(&C::META, &dir)?,
+                Source::Overrides(overrides) => {
+                    crate::values::from_overrides(&C::META, &overrides)?
+                }
+                Source::Fn(f) => f()?,
                 Source::Preloaded(p) => p,
             };
 
             partial = partial.with_fallback(layer);
+            if self.stop_when_complete && partial.is_complete() {
+                break;
+            }
         }
 
-        C::from_partial(partial.with_fallback(C::Partial::default_values()))
+        let out = C::from_partial(partial.with_fallback(C::Partial::default_values()));
+        if self.helpful_errors {
+            out.map_err(|e| e.with_missing_value_help(&C::META))
+        } else {
+            out
+        }
+    }
+
+    /// Like [`Builder::load`], but additionally returns the warnings of all
+    /// `#[config(warn(...))]` attributes whose condition holds for the loaded
+    /// configuration (see [`Config::warnings`]). Warnings don't prevent
+    /// loading; it's up to you to show them to the user.
+    ///
+    /// ```
+    /// use confique::Config;
+    ///
+    /// #[derive(Config)]
+    /// #[config(warn(*port < 1024, "ports below 1024 usually require root"))]
+    /// struct Conf {
+    ///     #[config(default = 80)]
+    ///     port: u16,
+    /// }
+    ///
+    /// let (conf, warnings) = Conf::builder().load_with_warnings()?;
+    /// assert_eq!(conf.port, 80);
+    /// assert_eq!(warnings[0].to_string(), "ports below 1024 usually require root");
+    /// # Ok::<_, confique::Error>(())
+    /// ```
+    pub fn load_with_warnings(self) -> Result<(C, Vec<Warning>), Error> {
+        let config = self.load()?;
+        let warnings = config.warnings();
+        Ok((config, warnings))
     }
 }
 
+type LookupFn = Box<dyn FnMut(&str) -> Option<String> + Send>;
+type LoadFn<C> = Box<dyn FnOnce() -> Result<<C as Config>::Partial, Error> + Send>;
+
 enum Source<C: Config> {
     #[cfg(any(feature = "toml", feature = "yaml", feature = "json5"))]
     File(PathBuf),
+    #[cfg(any(feature = "toml", feature = "yaml", feature = "json5"))]
+    Embedded(&'static str, FileFormat),
     Env,
+    Lookup(LookupFn),
+    Overrides(Vec<String>),
+    DirAsKeys(PathBuf),
+    Fn(LoadFn<C>),
     Preloaded(C::Partial),
 }
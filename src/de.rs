@@ -0,0 +1,197 @@
+//! Functions for the `#[config(deserialize_with = ...)]` attribute, to
+//! deserialize human-friendly values.
+//!
+//! These work for all sources: values can be specified as numbers or as
+//! strings in files, and as strings in environment variables. This is the
+//! `serde` counterpart of the [`env::parse`][crate::env::parse] functions.
+
+use std::{fmt, str::FromStr, time::Duration};
+
+use serde::de::{self, Deserializer, Visitor};
+
+
+/// Deserializes a number that can also be given as string with `_` as
+/// thousands separator (or in any other place), e.g. `"10_000"`.
+///
+/// # Example
+///
+/// ```
+/// use confique::Config;
+///
+/// #[derive(Config)]
+/// struct Conf {
+///     #[config(env = "MAX_USERS", deserialize_with = confique::de::number_with_underscores)]
+///     max_users: u32,
+/// }
+///
+/// std::env::set_var("MAX_USERS", "10_000");
+/// let conf = Conf::builder().env().load()?;
+/// assert_eq!(conf.max_users, 10_000);
+/// # Ok::<_, confique::Error>(())
+/// ```
+pub fn number_with_underscores<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    let s = deserializer.deserialize_any(NumberOrStringVisitor("a number"))?;
+    s.replace('_', "")
+        .parse()
+        .map_err(|e| de::Error::custom(format_args!("invalid number '{s}': {e}")))
+}
+
+/// Deserializes a size in bytes, given either as a number or as a string of
+/// an integer followed by an optional unit, e.g. `"512"`, `"64 kB"` or
+/// `"1MiB"`. The integer may contain `_` as thousands separator.
+///
+/// The supported units (case-insensitive) are `B`, the decimal units `kB`,
+/// `MB`, `GB`, `TB` (powers of 1000) and the binary units `KiB`, `MiB`,
+/// `GiB`, `TiB` (powers of 1024). Whitespace between number and unit is
+/// allowed.
+///
+/// # Example
+///
+/// ```
+/// use confique::Config;
+///
+/// #[derive(Config)]
+/// struct Conf {
+///     #[config(env = "CACHE_SIZE", deserialize_with = confique::de::bytesize)]
+///     cache_size: u64,
+/// }
+///
+/// std::env::set_var("CACHE_SIZE", "2 MiB");
+/// let conf = Conf::builder().env().load()?;
+/// assert_eq!(conf.cache_size, 2 * 1024 * 1024);
+/// # Ok::<_, confique::Error>(())
+/// ```
+pub fn bytesize<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: TryFrom<u64>,
+{
+    let s = deserializer.deserialize_any(NumberOrStringVisitor("a size in bytes"))?;
+    let bytes = parse_bytesize(&s)
+        .ok_or_else(|| de::Error::custom(format_args!("invalid size in bytes: '{s}'")))?;
+    T::try_from(bytes)
+        .map_err(|_| de::Error::custom(format_args!("size in bytes too large: '{s}'")))
+}
+
+fn parse_bytesize(s: &str) -> Option<u64> {
+    let s = s.trim();
+    let unit_start = s.find(|c: char| !c.is_ascii_digit() && c != '_').unwrap_or(s.len());
+    let (number, unit) = s.split_at(unit_start);
+    if number.is_empty() {
+        return None;
+    }
+
+    let number: u64 = number.replace('_', "").parse().ok()?;
+    let factor: u64 = match &*unit.trim_start().to_ascii_lowercase() {
+        "" | "b" => 1,
+        "kb" => 1000,
+        "mb" => 1000u64.pow(2),
+        "gb" => 1000u64.pow(3),
+        "tb" => 1000u64.pow(4),
+        "kib" => 1 << 10,
+        "mib" => 1 << 20,
+        "gib" => 1 << 30,
+        "tib" => 1 << 40,
+        _ => return None,
+    };
+
+    number.checked_mul(factor)
+}
+
+/// Deserializes a [`Duration`] from a number of seconds, given either as a
+/// number or as a string, e.g. `30`, `1.5` or `"90"`. Integers may contain
+/// `_` as thousands separator when given as string.
+///
+/// Without this, `Duration` uses its `serde` representation, which is a
+/// struct with `secs` and `nanos` fields, or an array `[secs, nanos]`. That's
+/// why `#[config(default = [30, 0])]` works for plain `Duration` fields while
+/// `#[config(default = 30)]` does not. With this function, the latter works
+/// and is the more readable choice.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use confique::Config;
+///
+/// #[derive(Config)]
+/// struct Conf {
+///     #[config(
+///         env = "TIMEOUT",
+///         default = 30,
+///         deserialize_with = confique::de::duration_seconds,
+///     )]
+///     timeout: std::time::Duration,
+/// }
+///
+/// let conf = Conf::builder().load()?;
+/// assert_eq!(conf.timeout, Duration::from_secs(30));
+///
+/// std::env::set_var("TIMEOUT", "2.5");
+/// let conf = Conf::builder().env().load()?;
+/// assert_eq!(conf.timeout, Duration::from_millis(2500));
+/// # Ok::<_, confique::Error>(())
+/// ```
+pub fn duration_seconds<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = deserializer.deserialize_any(NumberOrStringVisitor("a duration in seconds"))?;
+    parse_duration_seconds(&s)
+        .ok_or_else(|| de::Error::custom(format_args!("invalid duration in seconds: '{s}'")))
+}
+
+fn parse_duration_seconds(s: &str) -> Option<Duration> {
+    let s = s.trim().replace('_', "");
+    if let Ok(secs) = s.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+
+    // `Duration::from_secs_f64` panics for these values.
+    let secs = s.parse::<f64>().ok()?;
+    if !secs.is_finite() || secs < 0.0 || secs >= u64::MAX as f64 {
+        return None;
+    }
+    Some(Duration::from_secs_f64(secs))
+}
+
+/// Accepts numbers and strings and returns them as string. `.0` is the
+/// expectation for error messages.
+struct NumberOrStringVisitor(&'static str);
+
+macro_rules! visit_to_string {
+    ($($method:ident: $ty:ty),*) => {
+        $(
+            fn $method<E: de::Error>(self, v: $ty) -> Result<Self::Value, E> {
+                Ok(v.to_string())
+            }
+        )*
+    };
+}
+
+impl<'de> Visitor<'de> for NumberOrStringVisitor {
+    type Value = String;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (as number or string)", self.0)
+    }
+
+    visit_to_string!(
+        visit_i64: i64,
+        visit_i128: i128,
+        visit_u64: u64,
+        visit_u128: u128,
+        visit_f64: f64,
+        visit_str: &str
+    );
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<Self::Value, E> {
+        Ok(v)
+    }
+}
+
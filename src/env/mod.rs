@@ -53,6 +53,20 @@ impl Deserializer {
     }
 }
 
+/// Parses the bool values we accept in env vars. Expects a trimmed string.
+pub(crate) fn parse_bool(s: &str) -> Option<bool> {
+    match () {
+        () if s == "1"
+            || s.eq_ignore_ascii_case("true")
+            || s.eq_ignore_ascii_case("yes") => Some(true),
+
+        () if s == "0"
+            || s.eq_ignore_ascii_case("false")
+            || s.eq_ignore_ascii_case("no") => Some(false),
+        _ => None,
+    }
+}
+
 macro_rules! deserialize_via_parse {
     ($method:ident, $visit_method:ident, $int:ident) => {
         fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -86,18 +100,10 @@ impl<'de> serde::Deserializer<'de> for Deserializer {
         V: serde::de::Visitor<'de>,
     {
         let s = self.value.trim();
-        let v = match () {
-            () if s == "1"
-                || s.eq_ignore_ascii_case("true")
-                || s.eq_ignore_ascii_case("yes") => true,
-
-            () if s == "0"
-                || s.eq_ignore_ascii_case("false")
-                || s.eq_ignore_ascii_case("no") => false,
-            _ => return Err(DeError(format!("invalid value for bool: '{s}'"))),
-        };
-
-        visitor.visit_bool(v)
+        match parse_bool(s) {
+            Some(v) => visitor.visit_bool(v),
+            None => Err(DeError(format!("invalid value for bool: '{s}'"))),
+        }
     }
 
     deserialize_via_parse!(deserialize_i8, visit_i8, i8);
@@ -122,34 +128,143 @@ impl<'de> serde::Deserializer<'de> for Deserializer {
         visitor.visit_newtype_struct(self)
     }
 
+    // Empty strings are empty collections. Non-empty collections cannot be
+    // deserialized from env values without `parse_env`.
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        if self.value.is_empty() {
+            visitor.visit_seq(serde::de::value::SeqDeserializer::new(std::iter::empty::<()>()))
+        } else {
+            self.deserialize_any(visitor)
+        }
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        if self.value.is_empty() {
+            let empty = std::iter::empty::<((), ())>();
+            visitor.visit_map(serde::de::value::MapDeserializer::new(empty))
+        } else {
+            self.deserialize_any(visitor)
+        }
+    }
+
     fn deserialize_enum<V>(
         self,
         _name: &str,
-        _variants: &'static [&'static str],
+        variants: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value, Self::Error>
     where
         V: serde::de::Visitor<'de>,
     {
-        visitor.visit_enum(self.value.into_deserializer())
+        // Values that are exactly a variant name are not split, so that
+        // variant names containing `:` (via `serde(rename)`) still work.
+        let (variant, payload) = match self.value.split_once(':') {
+            Some((variant, payload)) if !variants.contains(&&*self.value) => {
+                (variant.to_owned(), Some(payload.to_owned()))
+            }
+            _ => (self.value, None),
+        };
+        visitor.visit_enum(EnumAccess { variant, payload })
     }
 
     serde::forward_to_deserialize_any! {
         char str string
         bytes byte_buf
         unit unit_struct
-        map
         option
         struct
         identifier
         ignored_any
 
         // TODO: think about manually implementing these
-        seq
         tuple tuple_struct
     }
 }
 
+/// Enum values are given as `Variant` for unit variants and as
+/// `Variant:payload` for newtype variants.
+struct EnumAccess {
+    variant: String,
+    payload: Option<String>,
+}
+
+impl<'de> serde::de::EnumAccess<'de> for EnumAccess {
+    type Error = DeError;
+    type Variant = VariantAccess;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
+    where
+        V: serde::de::DeserializeSeed<'de>,
+    {
+        let value = seed.deserialize(self.variant.clone().into_deserializer())?;
+        Ok((value, VariantAccess { variant: self.variant, payload: self.payload }))
+    }
+}
+
+struct VariantAccess {
+    variant: String,
+    payload: Option<String>,
+}
+
+impl<'de> serde::de::VariantAccess<'de> for VariantAccess {
+    type Error = DeError;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        match self.payload {
+            None => Ok(()),
+            Some(_) => Err(DeError(format!(
+                "enum variant `{}` does not take a value, specify it as just `{0}`",
+                self.variant,
+            ))),
+        }
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
+    where
+        T: serde::de::DeserializeSeed<'de>,
+    {
+        match self.payload {
+            Some(payload) => seed.deserialize(Deserializer::new(payload)),
+            None => Err(DeError(format!(
+                "enum variant `{}` requires a value, specify it as `{0}:<value>`",
+                self.variant,
+            ))),
+        }
+    }
+
+    fn tuple_variant<V>(self, _len: usize, _visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        Err(DeError(format!(
+            "enum variant `{}` is a tuple variant, which cannot be loaded from \
+                environment variables (only unit and newtype variants are supported)",
+            self.variant,
+        )))
+    }
+
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        Err(DeError(format!(
+            "enum variant `{}` is a struct variant, which cannot be loaded from \
+                environment variables (only unit and newtype variants are supported)",
+            self.variant,
+        )))
+    }
+}
+
 
 #[cfg(test)]
 mod tests;
@@ -1,9 +1,12 @@
 //! Functions for the `#[config(parse_env = ...)]` attribute.
 
-use std::str::FromStr;
+use std::{num::ParseIntError, str::FromStr};
 
 /// Splits the environment variable by separator `SEP`, parses each element
-/// with [`FromStr`] and collects everything via [`FromIterator`].
+/// with [`FromStr`] and collects everything via [`FromIterator`]. An empty
+/// string results in an empty collection, so that `PORTS=` can be used to
+/// explicitly set an empty list (instead of the env var being treated as
+/// unset).
 ///
 /// To avoid having to specify the separator via `::<>` syntax, see the
 /// other functions in this module.
@@ -33,6 +36,9 @@ where
     T: FromStr,
     C: FromIterator<T>,
 {
+    if input.is_empty() {
+        return Ok(std::iter::empty().collect());
+    }
     input.split(SEP).map(T::from_str).collect()
 }
 
@@ -54,3 +60,78 @@ specify_fn_wrapper!(list_by_comma, ',');
 specify_fn_wrapper!(list_by_semicolon, ';');
 specify_fn_wrapper!(list_by_colon, ':');
 specify_fn_wrapper!(list_by_space, ' ');
+
+
+/// Parses an integer, accepting the prefixes `0x` (hexadecimal), `0o` (octal)
+/// and `0b` (binary) in addition to plain decimal numbers. A sign (`-` or
+/// `+`) can be placed before the prefix, e.g. `-0x10`. Useful for values
+/// usually written in hexadecimal, like bit masks or permissions.
+///
+/// # Example
+///
+/// ```
+/// use confique::Config;
+///
+/// #[derive(Debug, confique::Config)]
+/// struct Conf {
+///     #[config(env = "MASK", parse_env = confique::env::parse::int_auto_radix)]
+///     mask: u32,
+///     #[config(env = "MODE", parse_env = confique::env::parse::int_auto_radix)]
+///     mode: u16,
+/// }
+///
+/// std::env::set_var("MASK", "0xFF00");
+/// std::env::set_var("MODE", "0o644");
+/// let conf = Conf::builder().env().load()?;
+/// assert_eq!(conf.mask, 0xFF00);
+/// assert_eq!(conf.mode, 0o644);
+/// # Ok::<_, confique::Error>(())
+/// ```
+pub fn int_auto_radix<T: FromStrRadix>(input: &str) -> Result<T, ParseIntError> {
+    let (sign, rest) = match input.as_bytes().first() {
+        Some(b'-') => ("-", &input[1..]),
+        Some(b'+') => ("", &input[1..]),
+        _ => ("", input),
+    };
+
+    let prefix = rest.get(..2).map(str::to_ascii_lowercase);
+    let (radix, digits) = match prefix.as_deref() {
+        Some("0x") => (16, &rest[2..]),
+        Some("0o") => (8, &rest[2..]),
+        Some("0b") => (2, &rest[2..]),
+        _ => (10, rest),
+    };
+
+    // `from_str_radix` accepts a sign itself, which must not appear again
+    // after the prefix or the first sign. A lone sign results in an "invalid
+    // digit" error, as `ParseIntError` cannot be constructed otherwise.
+    if digits.starts_with(['-', '+']) {
+        return T::from_str_radix("+", radix);
+    }
+
+    if sign.is_empty() {
+        T::from_str_radix(digits, radix)
+    } else {
+        T::from_str_radix(&format!("{sign}{digits}"), radix)
+    }
+}
+
+/// Integer types that can be parsed from a string with a given radix. Used by
+/// [`int_auto_radix`].
+pub trait FromStrRadix: Sized {
+    fn from_str_radix(src: &str, radix: u32) -> Result<Self, ParseIntError>;
+}
+
+macro_rules! impl_from_str_radix {
+    ($($ty:ident),*) => {
+        $(
+            impl FromStrRadix for $ty {
+                fn from_str_radix(src: &str, radix: u32) -> Result<Self, ParseIntError> {
+                    $ty::from_str_radix(src, radix)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_str_radix!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
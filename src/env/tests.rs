@@ -41,3 +41,76 @@ fn floats() {
     assert_eq!(de("3.1415"), Ok(3.1415f32));
     assert_eq!(de("-123.456"), Ok(-123.456f64));
 }
+
+#[test]
+fn enums() {
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    enum Level {
+        Off,
+        #[serde(rename = "on:all")]
+        All,
+        Custom(u8),
+        Named(String),
+        Pair(u8, u8),
+        Range { min: u8 },
+    }
+
+    assert_eq!(de("Off"), Ok(Level::Off));
+    assert_eq!(de("on:all"), Ok(Level::All));
+    assert_eq!(de("Custom:3"), Ok(Level::Custom(3)));
+    assert_eq!(de("Custom: 3 "), Ok(Level::Custom(3)));
+    assert_eq!(de("Named:a:b"), Ok(Level::Named("a:b".into())));
+
+    assert_eq!(de::<Level>("Of"), Err(DeError("unknown variant `Of`, expected one of \
+        `Off`, `on:all`, `Custom`, `Named`, `Pair`, `Range`".into())));
+    assert_eq!(de::<Level>("Off:1"), Err(DeError(
+        "enum variant `Off` does not take a value, specify it as just `Off`".into())));
+    assert_eq!(de::<Level>("Custom"), Err(DeError(
+        "enum variant `Custom` requires a value, specify it as `Custom:<value>`".into())));
+    assert_eq!(de::<Level>("Custom:x"), Err(DeError(
+        "invalid value 'x' for type u8: invalid digit found in string".into())));
+    assert_eq!(de::<Level>("Pair:1"), Err(DeError(
+        "enum variant `Pair` is a tuple variant, which cannot be loaded from environment \
+            variables (only unit and newtype variants are supported)".into())));
+    assert_eq!(de::<Level>("Range"), Err(DeError(
+        "enum variant `Range` is a struct variant, which cannot be loaded from environment \
+            variables (only unit and newtype variants are supported)".into())));
+}
+
+#[test]
+fn empty_collections() {
+    use std::collections::HashMap;
+
+    assert_eq!(de(""), Ok(Vec::<String>::new()));
+    assert_eq!(de(""), Ok(Vec::<u32>::new()));
+    assert_eq!(de(""), Ok(HashMap::<String, u32>::new()));
+    assert!(de::<Vec<String>>("a").is_err());
+    assert!(de::<Vec<String>>(" ").is_err());
+}
+
+#[test]
+fn int_auto_radix() {
+    use super::parse::int_auto_radix;
+
+    assert_eq!(int_auto_radix("255"), Ok(255u8));
+    assert_eq!(int_auto_radix("0xff"), Ok(255u8));
+    assert_eq!(int_auto_radix("0XFF"), Ok(255u8));
+    assert_eq!(int_auto_radix("0o777"), Ok(0o777u16));
+    assert_eq!(int_auto_radix("0b1010"), Ok(10u32));
+    assert_eq!(int_auto_radix("0"), Ok(0u32));
+    assert_eq!(int_auto_radix("010"), Ok(10u32));
+    assert_eq!(int_auto_radix("-0x80"), Ok(-128i8));
+    assert_eq!(int_auto_radix("+0b11"), Ok(3i64));
+    assert_eq!(int_auto_radix("-42"), Ok(-42i32));
+
+    assert!(int_auto_radix::<u8>("0x100").is_err());
+    assert!(int_auto_radix::<u8>("0x").is_err());
+    assert!(int_auto_radix::<u8>("0b2").is_err());
+    assert!(int_auto_radix::<u8>("0xg").is_err());
+    assert!(int_auto_radix::<u8>("-0x1").is_err());
+    assert!(int_auto_radix::<i8>("0x-1").is_err());
+    assert!(int_auto_radix::<i8>("--1").is_err());
+    assert!(int_auto_radix::<i8>("+-1").is_err());
+    assert!(int_auto_radix::<u8>("").is_err());
+    assert!(int_auto_radix::<u8>(" 1").is_err());
+}
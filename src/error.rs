@@ -1,6 +1,8 @@
 use std::fmt;
 
-use std::path::PathBuf;
+use std::{path::PathBuf, time::Duration};
+
+use crate::meta::{FieldKind, Meta};
 
 
 
@@ -12,12 +14,71 @@ use std::path::PathBuf;
 ///  Crates like `anyhow` and `eyre` do this for you. As a convenience feature,
 ///  you can use the "alternate" flag `#` when printing this error to include
 ///  the source, e.g. `println!("{:#}", err)`. This will only print the direct
-///  source though, so a proper traversal is still preferred!
+///  source though, so a proper traversal is still preferred! Or use
+///  [`Error::display_chain`] if you don't use any of these crates.
 pub struct Error {
     pub(crate) inner: Box<ErrorInner>,
 }
 
 impl Error {
+    /// Returns a type that displays this error followed by its full `source`
+    /// chain, all joined by `": "`. Useful to show complete error messages
+    /// to end users without crates like `anyhow` or `eyre`.
+    ///
+    /// ```
+    /// use confique::Config;
+    ///
+    /// #[derive(Config)]
+    /// struct Conf {
+    ///     #[config(env = "DISPLAY_CHAIN_DOC_PORT", parse_env = str::parse::<u16>)]
+    ///     port: u16,
+    /// }
+    ///
+    /// std::env::set_var("DISPLAY_CHAIN_DOC_PORT", "not a number");
+    /// let err = Conf::builder().env().load().err().unwrap();
+    /// assert_eq!(
+    ///     err.display_chain().to_string(),
+    ///     "failed to parse environment variable `DISPLAY_CHAIN_DOC_PORT` into field \
+    ///         `Conf::port`: invalid digit found in string",
+    /// );
+    /// ```
+    pub fn display_chain(&self) -> impl fmt::Display + '_ {
+        DisplayChain(self)
+    }
+
+    /// Adds a help text to `MissingValue` errors, mentioning the field's env
+    /// key and doc comment (if any). Other errors are returned unchanged.
+    pub(crate) fn with_missing_value_help(self, meta: &Meta) -> Self {
+        let (path, field) = match &*self.inner {
+            ErrorInner::MissingValue(path) => match meta.find(path) {
+                Some(field) => (path, field),
+                None => return self,
+            },
+            _ => return self,
+        };
+
+        let mut help = match &field.kind {
+            FieldKind::Leaf { env: Some(env), .. } => format!(
+                "set it in a configuration file or via environment variable `{env}`",
+            ),
+            _ => "set it in a configuration file".to_owned(),
+        };
+        let doc = field.doc.iter().map(|l| l.trim()).collect::<Vec<_>>().join(" ");
+        if !doc.trim().is_empty() {
+            help.push_str("; ");
+            help.push_str(doc.trim());
+        }
+
+        ErrorInner::MissingValueWithHelp { path: path.clone(), help }.into()
+    }
+
+    /// Creates an error for a custom source (see [`Builder::source_fn`][
+    /// crate::Builder::source_fn]) that failed to load. `err` is returned by
+    /// `Error::source`.
+    pub fn custom_source(err: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> Self {
+        ErrorInner::CustomSource { err: err.into() }.into()
+    }
+
     pub(crate) fn field_validation(msg: impl fmt::Display) -> Self {
         ErrorInner::FieldValidation { msg: msg.to_string() }.into()
     }
@@ -37,6 +98,10 @@ pub(crate) enum ErrorInner {
     /// human-readable path to the value, e.g. `http.port`.
     MissingValue(String),
 
+    /// Like `MissingValue`, but with a help text built from the field's
+    /// metadata (see `Builder::helpful_errors`).
+    MissingValueWithHelp { path: String, help: String },
+
     /// An IO error occured, e.g. when reading a file.
     Io {
         path: Option<PathBuf>,
@@ -76,6 +141,10 @@ pub(crate) enum ErrorInner {
     /// feature of confique was not enabled.
     UnsupportedFileFormat { path: PathBuf },
 
+    /// Returned by `File::new` if the file extension belongs to a format that
+    /// is supported by confique, but whose Cargo feature is not enabled.
+    DisabledFileFormat { path: PathBuf, feature: &'static str },
+
     /// Returned by the [`Source`] impls for `Path` and `PathBuf` if the path
     /// does not contain a file extension.
     MissingFileExtension { path: PathBuf },
@@ -83,11 +152,39 @@ pub(crate) enum ErrorInner {
     /// A file source was marked as required but the file does not exist.
     MissingRequiredFile { path: PathBuf },
 
+    /// A file source was marked as required and exists, but is empty or only
+    /// contains whitespace.
+    EmptyRequiredFile { path: PathBuf },
+
+    /// A file source with `require_known_keys` set contains keys, but none of
+    /// them are known configuration values.
+    NoKnownKeys { path: PathBuf },
+
+    /// An override passed to `Builder::overrides` is malformed or refers to
+    /// an unknown value.
+    InvalidOverride { value: String, msg: &'static str },
+
+    /// A string-based source (e.g. `Builder::overrides`) contains a value for
+    /// a field of an `extend` field, which cannot be deserialized from
+    /// strings.
+    ExtendedFieldValue { path: String, source: String },
+
     /// When a field validation function fails.
     FieldValidation { msg: String },
 
     /// When a struct validation function fails.
     StructValidation { name: String, msg: String },
+
+    /// A source added via `Builder::source_with_timeout` did not finish
+    /// loading in time.
+    SourceTimeout { timeout: Duration },
+
+    /// A custom source failed, created via `Error::custom_source`.
+    CustomSource { err: Box<dyn std::error::Error + Send + Sync> },
+
+    /// When serializing a configuration (e.g. to emit it with its values)
+    /// fails.
+    Serialization { err: Box<dyn std::error::Error + Send + Sync> },
 }
 
 impl std::error::Error for Error {
@@ -96,14 +193,23 @@ impl std::error::Error for Error {
             ErrorInner::Io { err, .. } => Some(err),
             ErrorInner::Deserialization { err, .. } => Some(&**err),
             ErrorInner::MissingValue(_) => None,
+            ErrorInner::MissingValueWithHelp { .. } => None,
             ErrorInner::EnvNotUnicode { .. } => None,
             ErrorInner::EnvDeserialization { .. } => None,
             ErrorInner::EnvParseError { err, .. } => Some(&**err),
             ErrorInner::UnsupportedFileFormat { .. } => None,
+            ErrorInner::DisabledFileFormat { .. } => None,
             ErrorInner::MissingFileExtension { .. } => None,
             ErrorInner::MissingRequiredFile { .. } => None,
+            ErrorInner::EmptyRequiredFile { .. } => None,
+            ErrorInner::NoKnownKeys { .. } => None,
+            ErrorInner::InvalidOverride { .. } => None,
+            ErrorInner::ExtendedFieldValue { .. } => None,
             ErrorInner::FieldValidation { .. } => None,
             ErrorInner::StructValidation { .. } => None,
+            ErrorInner::SourceTimeout { .. } => None,
+            ErrorInner::CustomSource { err } => Some(&**err),
+            ErrorInner::Serialization { err } => Some(&**err),
         }
     }
 }
@@ -114,6 +220,9 @@ impl fmt::Display for Error {
             ErrorInner::MissingValue(path) => {
                 std::write!(f, "required configuration value is missing: '{path}'")
             }
+            ErrorInner::MissingValueWithHelp { path, help } => {
+                std::write!(f, "required configuration value is missing: '{path}'; {help}")
+            }
             ErrorInner::Io { path: Some(path), .. } => {
                 std::write!(f,
                     "IO error occured while reading configuration file '{}'",
@@ -162,6 +271,13 @@ impl fmt::Display for Error {
                     path.display(),
                 )
             }
+            ErrorInner::DisabledFileFormat { path, feature } => {
+                std::write!(f,
+                    "cannot load configuration file '{}': support for this file format \
+                        requires enabling the `{feature}` feature of confique",
+                    path.display(),
+                )
+            }
             ErrorInner::MissingFileExtension { path } => {
                 std::write!(f,
                     "cannot guess configuration file format due to missing file extension in '{}'",
@@ -174,16 +290,76 @@ impl fmt::Display for Error {
                     path.display(),
                 )
             }
+            ErrorInner::EmptyRequiredFile { path } => {
+                std::write!(f,
+                    "required configuration file is empty: '{}' (it has to contain \
+                        configuration values)",
+                    path.display(),
+                )
+            }
+            ErrorInner::NoKnownKeys { path } => {
+                std::write!(f,
+                    "configuration file '{}' does not contain any known configuration \
+                        values (is it the right file?)",
+                    path.display(),
+                )
+            }
+            ErrorInner::InvalidOverride { value, msg } => {
+                std::write!(f, "invalid configuration override '{value}': {msg}")
+            }
+            ErrorInner::ExtendedFieldValue { path, source } => {
+                std::write!(f,
+                    "cannot load '{path}' from {source}: values of fields included via \
+                        `#[config(extend)]` can only be loaded from files and environment \
+                        variables",
+                )
+            }
             ErrorInner::FieldValidation { msg } => {
                 std::write!(f, "validation failed: {msg}")
             }
             ErrorInner::StructValidation { name, msg } => {
                 std::write!(f, "config validation of `{name}` failed: {msg}")
             }
+            ErrorInner::SourceTimeout { timeout } => {
+                std::write!(f, "configuration source did not finish loading within {timeout:?}")
+            }
+            ErrorInner::CustomSource { err } => {
+                std::write!(f, "failed to load configuration from custom source")?;
+                if f.alternate() {
+                    f.write_str(": ")?;
+                    fmt::Display::fmt(&err, f)?;
+                }
+                Ok(())
+            }
+            ErrorInner::Serialization { err } => {
+                std::write!(f, "failed to serialize configuration")?;
+                if f.alternate() {
+                    f.write_str(": ")?;
+                    fmt::Display::fmt(&err, f)?;
+                }
+                Ok(())
+            }
         }
     }
 }
 
+/// Returned by `Error::display_chain`.
+struct DisplayChain<'a>(&'a Error);
+
+impl fmt::Display for DisplayChain<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Printing without the alternate flag, as otherwise the direct source
+        // would be printed twice.
+        write!(f, "{}", self.0)?;
+        let mut source = std::error::Error::source(self.0);
+        while let Some(err) = source {
+            write!(f, ": {err}")?;
+            source = err.source();
+        }
+        Ok(())
+    }
+}
+
 impl fmt::Debug for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         fmt::Display::fmt(self, f)
@@ -195,3 +371,39 @@ impl From<ErrorInner> for Error {
         Self { inner: Box::new(inner) }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{fmt, io};
+    use super::{Error, ErrorInner};
+
+    #[derive(Debug)]
+    struct Outer(io::Error);
+
+    impl fmt::Display for Outer {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("outer")
+        }
+    }
+
+    impl std::error::Error for Outer {
+        fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+            Some(&self.0)
+        }
+    }
+
+    #[test]
+    fn display_chain() {
+        let err: Error = ErrorInner::FieldValidation { msg: "bad".into() }.into();
+        assert_eq!(err.display_chain().to_string(), "validation failed: bad");
+
+        let err: Error = ErrorInner::Deserialization {
+            source: Some("file 'foo.toml'".into()),
+            err: Box::new(Outer(io::Error::new(io::ErrorKind::Other, "inner"))),
+        }.into();
+        assert_eq!(
+            err.display_chain().to_string(),
+            "failed to deserialize configuration from file 'foo.toml': outer: inner",
+        );
+    }
+}
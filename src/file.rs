@@ -1,4 +1,6 @@
-use std::{ffi::OsStr, fs, io, path::PathBuf};
+use std::{collections::BTreeMap, ffi::OsStr, fs, io, path::PathBuf};
+
+use serde::{de::{DeserializeOwned, IgnoredAny}, Serialize};
 
 use crate::{error::ErrorInner, Error, Partial};
 
@@ -12,6 +14,8 @@ pub struct File {
     path: PathBuf,
     format: FileFormat,
     required: bool,
+    allow_empty: bool,
+    require_known_keys: bool,
 }
 
 impl File {
@@ -23,8 +27,12 @@ impl File {
         let ext = path
             .extension()
             .ok_or_else(|| ErrorInner::MissingFileExtension { path: path.clone() })?;
-        let format = FileFormat::from_extension(ext)
-            .ok_or_else(|| ErrorInner::UnsupportedFileFormat { path: path.clone() })?;
+        let format = FileFormat::from_extension(ext).ok_or_else(|| {
+            match FileFormat::feature_for_extension(ext) {
+                Some(feature) => ErrorInner::DisabledFileFormat { path: path.clone(), feature },
+                None => ErrorInner::UnsupportedFileFormat { path: path.clone() },
+            }
+        })?;
 
         Ok(Self::with_format(path, format))
     }
@@ -35,17 +43,44 @@ impl File {
             path: path.into(),
             format,
             required: false,
+            allow_empty: false,
+            require_known_keys: false,
         }
     }
 
     /// Marks this file as required, meaning that [`File::load`] will return an
     /// error if the file does not exist. Otherwise, an empty layer (all values
     /// are `None`) is returned.
+    ///
+    /// A required file that exists but is empty (or only contains whitespace)
+    /// also results in an error, unless [`File::allow_empty`] is used.
     pub fn required(mut self) -> Self {
         self.required = true;
         self
     }
 
+    /// Allows a [required][File::required] file to be empty, in which case an
+    /// empty layer is returned, e.g. if all values have defaults anyway. Has
+    /// no effect on files that are not required, as those may always be
+    /// empty.
+    pub fn allow_empty(mut self) -> Self {
+        self.allow_empty = true;
+        self
+    }
+
+    /// Makes [`File::load`] return an error if the file contains values, but
+    /// none of them are known to the configuration, i.e. the loaded layer is
+    /// empty although the file is not. Files that don't exist or contain no
+    /// values at all are not affected.
+    ///
+    /// This is a guard against misconfigured paths, e.g. a `--config`
+    /// argument pointing to some unrelated file, which would otherwise be
+    /// silently ignored.
+    pub fn require_known_keys(mut self) -> Self {
+        self.require_known_keys = true;
+        self
+    }
+
     /// Attempts to load the file into the partial configuration `P`.
     pub fn load<P: Partial>(&self) -> Result<P, Error> {
         // Load file contents. If the file does not exist and was not marked as
@@ -67,37 +102,35 @@ impl File {
             }
         };
 
-        // Helper closure to create an error.
-        let error = |err| {
-            Error::from(ErrorInner::Deserialization {
-                err,
-                source: Some(format!("file '{}'", self.path.display())),
-            })
-        };
+        if self.required
+            && !self.allow_empty
+            && file_content.iter().all(|b| b.is_ascii_whitespace())
+        {
+            return Err(ErrorInner::EmptyRequiredFile { path: self.path.clone() }.into());
+        }
 
-        match self.format {
-            #[cfg(feature = "toml")]
-            FileFormat::Toml => {
-                let s = std::str::from_utf8(&file_content).map_err(|e| error(Box::new(e)))?;
-                toml::from_str(s).map_err(|e| error(Box::new(e)))
+        let partial: P = self.parse(&file_content)?;
+        if self.require_known_keys && partial.is_empty() {
+            // If the file cannot be parsed as map, it contains no keys at all.
+            let has_keys = self.parse::<BTreeMap<String, IgnoredAny>>(&file_content)
+                .map_or(false, |map| !map.is_empty());
+            if has_keys {
+                return Err(ErrorInner::NoKnownKeys { path: self.path.clone() }.into());
             }
+        }
 
-            #[cfg(feature = "yaml")]
-            FileFormat::Yaml => serde_yaml::from_slice(&file_content)
-                .map_err(|e| error(Box::new(e))),
+        Ok(partial)
+    }
 
-            #[cfg(feature = "json5")]
-            FileFormat::Json5 => {
-                let s = std::str::from_utf8(&file_content).map_err(|e| error(Box::new(e)))?;
-                json5::from_str(s).map_err(|e| error(Box::new(e)))
-            }
-        }
+    fn parse<T: DeserializeOwned>(&self, file_content: &[u8]) -> Result<T, Error> {
+        self.format.parse(file_content, || format!("file '{}'", self.path.display()))
     }
 }
 
 /// All file formats supported by confique.
 ///
 /// All enum variants are `#[cfg]` guarded with the respective crate feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FileFormat {
     #[cfg(feature = "toml")]
     Toml,
@@ -124,4 +157,139 @@ impl FileFormat {
             _ => None,
         }
     }
+
+    /// Deserializes `content` in this format. `source` describes where the
+    /// content comes from and is only called in case of an error (see
+    /// `ErrorInner::Deserialization`).
+    pub(crate) fn parse<T: DeserializeOwned>(
+        self,
+        content: &[u8],
+        source: impl Fn() -> String,
+    ) -> Result<T, Error> {
+        // Helper closure to create an error.
+        let error = |err| {
+            Error::from(ErrorInner::Deserialization { err, source: Some(source()) })
+        };
+
+        match self {
+            #[cfg(feature = "toml")]
+            FileFormat::Toml => {
+                let s = std::str::from_utf8(content).map_err(|e| error(Box::new(e)))?;
+                toml::from_str(s).map_err(|e| error(Box::new(e)))
+            }
+
+            #[cfg(feature = "yaml")]
+            FileFormat::Yaml => serde_yaml::from_slice(content)
+                .map_err(|e| error(Box::new(e))),
+
+            #[cfg(feature = "json5")]
+            FileFormat::Json5 => {
+                let s = std::str::from_utf8(content).map_err(|e| error(Box::new(e)))?;
+                json5::from_str(s).map_err(|e| error(Box::new(e)))
+            }
+        }
+    }
+
+    /// Serializes `value` in this format. Used by [`Config::dump`][crate::Config::dump].
+    pub(crate) fn serialize<T: Serialize>(self, value: &T) -> Result<String, Error> {
+        let error = |err| Error::from(ErrorInner::Serialization { err });
+
+        match self {
+            #[cfg(feature = "toml")]
+            FileFormat::Toml => toml::to_string(value).map_err(|e| error(Box::new(e))),
+
+            #[cfg(feature = "yaml")]
+            FileFormat::Yaml => serde_yaml::to_string(value).map_err(|e| error(Box::new(e))),
+
+            #[cfg(feature = "json5")]
+            FileFormat::Json5 => json5::to_string(value).map_err(|e| error(Box::new(e))),
+        }
+    }
+
+    /// Returns the name of the crate feature required for the format with the
+    /// given extension, regardless of whether it is enabled.
+    fn feature_for_extension(ext: &OsStr) -> Option<&'static str> {
+        match ext.to_str()? {
+            "toml" => Some("toml"),
+            "yaml" | "yml" => Some("yaml"),
+            "json5" | "json" => Some("json5"),
+            _ => None,
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::File;
+
+    #[cfg(feature = "toml")]
+    use super::FileFormat;
+
+    #[cfg(not(all(feature = "toml", feature = "yaml", feature = "json5")))]
+    fn assert_disabled(path: &str, feature: &str) {
+        let err = File::new(path).err().expect("expected error for disabled format");
+        assert_eq!(err.to_string(), format!(
+            "cannot load configuration file '{path}': support for this file format \
+                requires enabling the `{feature}` feature of confique",
+        ));
+    }
+
+    #[test]
+    fn disabled_format() {
+        #[cfg(not(feature = "toml"))]
+        assert_disabled("foo.toml", "toml");
+
+        #[cfg(not(feature = "yaml"))]
+        assert_disabled("foo.yml", "yaml");
+
+        #[cfg(not(feature = "json5"))]
+        assert_disabled("foo.json", "json5");
+    }
+
+    #[test]
+    fn unknown_format() {
+        let err = File::new("foo.ini").err().unwrap();
+        assert_eq!(err.to_string(), "unknown configuration file format/extension: 'foo.ini'");
+    }
+
+    /// Dumps a config, parses it again and checks that dumping the result
+    /// yields the same output.
+    #[cfg(feature = "toml")]
+    fn assert_dump_roundtrip(format: FileFormat, expected_prefix: &str) {
+        use crate::{test_utils::example1::Conf, Config, Partial};
+
+        let partial: <Conf as Config>::Partial = ::toml::from_str(
+            "site_name = \"Peter Pan's Tie Shop\"\n\
+                http.port = 80\n\
+                http.headers.score = { cookie = 1.5 }\n\
+                log.file = \"/tmp/x\"",
+        ).unwrap();
+        let partial = partial.with_fallback(Partial::default_values());
+        let conf = Conf::from_partial(partial).unwrap();
+        let dumped = conf.dump(format).unwrap();
+        assert!(dumped.starts_with(expected_prefix), "unexpected output:\n{dumped}");
+
+        let file = File::with_format("dumped", format);
+        let reparsed = Conf::from_partial(file.parse(dumped.as_bytes()).unwrap()).unwrap();
+        assert_eq!(reparsed.dump(format).unwrap(), dumped);
+    }
+
+    #[test]
+    #[cfg(feature = "toml")]
+    fn dump_toml() {
+        assert_dump_roundtrip(FileFormat::Toml, "site_name = \"Peter Pan's Tie Shop\"\n");
+    }
+
+    #[test]
+    #[cfg(all(feature = "toml", feature = "yaml"))]
+    fn dump_yaml() {
+        assert_dump_roundtrip(FileFormat::Yaml, "site_name: Peter Pan's Tie Shop\nhttp:\n");
+    }
+
+    #[test]
+    #[cfg(all(feature = "toml", feature = "json5"))]
+    fn dump_json5() {
+        assert_dump_roundtrip(FileFormat::Json5, "{\"site_name\":\"Peter Pan's Tie Shop\",");
+    }
 }
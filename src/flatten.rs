@@ -0,0 +1,646 @@
+//! Flattening partial configurations into maps of dotted paths to values.
+
+use std::{collections::BTreeMap, fmt};
+
+use serde::ser::{self, Impossible, Serialize, Serializer};
+
+use crate::{error::ErrorInner, Error, Partial};
+
+#[cfg(feature = "log")]
+use crate::{
+    meta::{FieldKind, Meta},
+    Config,
+};
+
+
+/// Flattens a partial configuration into a map from paths (e.g. `http.port`)
+/// to the stringified values of all fields that are set. This is useful for
+/// diagnostics and logging the "effective configuration" in a grep-friendly
+/// format. Requires the partial type to implement `Serialize`, which you can
+/// achieve with `#[config(partial_attr(derive(serde::Serialize)))]`.
+///
+/// Values are converted to strings: strings are used as they are, numbers,
+/// booleans and unit enum variants are formatted as usual. Arrays and maps
+/// are written like `[1, 2]` and `{"a": 1}`, with strings inside them quoted.
+/// Fields that are not set (`None`) are not included.
+///
+/// ```
+/// use confique::{Config, Partial};
+///
+/// #[derive(Config)]
+/// #[config(partial_attr(derive(serde::Serialize)))]
+/// struct Conf {
+///     name: Option<String>,
+///     #[config(nested)]
+///     http: HttpConf,
+/// }
+///
+/// #[derive(Config)]
+/// #[config(partial_attr(derive(serde::Serialize)))]
+/// struct HttpConf {
+///     #[config(default = 8080)]
+///     port: u16,
+///     #[config(default = ["a", "b"])]
+///     hosts: Vec<String>,
+/// }
+///
+/// # fn main() -> Result<(), confique::Error> {
+/// let partial = <Conf as Config>::Partial::default_values();
+/// let map = confique::flatten_partial(&partial)?;
+/// assert_eq!(map.len(), 2);
+/// assert_eq!(map["http.port"], "8080");
+/// assert_eq!(map["http.hosts"], r#"["a", "b"]"#);
+/// # Ok(())
+/// # }
+/// ```
+pub fn flatten_partial<P: Partial + Serialize>(
+    partial: &P,
+) -> Result<BTreeMap<String, String>, Error> {
+    let mut out = BTreeMap::new();
+    partial.serialize(FieldSerializer { path: String::new(), out: &mut out })
+        .map_err(|e| ErrorInner::Serialization { err: Box::new(e) })?;
+    Ok(out)
+}
+
+/// Flattens a full configuration into a list of paths and stringified values
+/// (like `flatten_partial`) in declaration order, consulting `C::META` to
+/// find nested fields. Values of fields marked with `#[config(secret)]` and
+/// of fields not found in the meta information are replaced by [`REDACTED`].
+#[cfg(feature = "log")]
+pub(crate) fn flatten_config<C: Config + Serialize>(
+    config: &C,
+) -> Result<Vec<(String, String)>, Error> {
+    let mut out = Vec::new();
+    config.serialize(ConfigSerializer { path: String::new(), meta: &C::META, out: &mut out })
+        .map_err(|e| ErrorInner::Serialization { err: Box::new(e) })?;
+    Ok(out)
+}
+
+/// Replacement for the values of secret fields.
+#[cfg(feature = "log")]
+pub(crate) const REDACTED: &str = "***";
+
+
+#[derive(Debug)]
+struct SerError(String);
+
+impl std::error::Error for SerError {}
+
+impl fmt::Display for SerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl ser::Error for SerError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self(msg.to_string())
+    }
+}
+
+fn join_path(prefix: &str, name: &str) -> String {
+    if prefix.is_empty() {
+        name.into()
+    } else {
+        format!("{prefix}.{name}")
+    }
+}
+
+
+/// Serializes a field of a partial type: leaf fields are `Option`s and nested
+/// partial types are structs.
+struct FieldSerializer<'a> {
+    path: String,
+    out: &'a mut BTreeMap<String, String>,
+}
+
+macro_rules! unexpected {
+    ($expected:literal; $( $method:ident ( $($arg:ident: $ty:ty),* ) -> $ret:ty ),* $(,)?) => {
+        $(
+            fn $method(self, $(_: $ty),*) -> Result<$ret, Self::Error> {
+                Err(SerError(format!(
+                    concat!("unexpected value at '{}': expected ", $expected),
+                    self.path,
+                )))
+            }
+        )*
+    };
+}
+
+impl<'a> Serializer for FieldSerializer<'a> {
+    type Ok = ();
+    type Error = SerError;
+    type SerializeSeq = Impossible<(), SerError>;
+    type SerializeTuple = Impossible<(), SerError>;
+    type SerializeTupleStruct = Impossible<(), SerError>;
+    type SerializeTupleVariant = Impossible<(), SerError>;
+    type SerializeMap = Impossible<(), SerError>;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Impossible<(), SerError>;
+
+    fn serialize_none(self) -> Result<(), SerError> {
+        Ok(())
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<(), SerError> {
+        let mut s = String::new();
+        value.serialize(ValueSerializer { out: &mut s, quote_strings: false })?;
+        self.out.insert(self.path, s);
+        Ok(())
+    }
+
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<Self, SerError> {
+        Ok(self)
+    }
+
+    unexpected!(
+        "partial configuration type";
+        serialize_bool(v: bool) -> (),
+        serialize_i8(v: i8) -> (),
+        serialize_i16(v: i16) -> (),
+        serialize_i32(v: i32) -> (),
+        serialize_i64(v: i64) -> (),
+        serialize_u8(v: u8) -> (),
+        serialize_u16(v: u16) -> (),
+        serialize_u32(v: u32) -> (),
+        serialize_u64(v: u64) -> (),
+        serialize_f32(v: f32) -> (),
+        serialize_f64(v: f64) -> (),
+        serialize_char(v: char) -> (),
+        serialize_str(v: &str) -> (),
+        serialize_bytes(v: &[u8]) -> (),
+        serialize_unit() -> (),
+        serialize_unit_struct(name: &'static str) -> (),
+        serialize_unit_variant(name: &'static str, index: u32, variant: &'static str) -> (),
+        serialize_seq(len: Option<usize>) -> Self::SerializeSeq,
+        serialize_tuple(len: usize) -> Self::SerializeTuple,
+        serialize_tuple_struct(name: &'static str, len: usize) -> Self::SerializeTupleStruct,
+        serialize_tuple_variant(
+            name: &'static str,
+            index: u32,
+            variant: &'static str,
+            len: usize
+        ) -> Self::SerializeTupleVariant,
+        serialize_map(len: Option<usize>) -> Self::SerializeMap,
+        serialize_struct_variant(
+            name: &'static str,
+            index: u32,
+            variant: &'static str,
+            len: usize
+        ) -> Self::SerializeStructVariant,
+    );
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<(), SerError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: &T,
+    ) -> Result<(), SerError> {
+        self.serialize_unit()
+    }
+}
+
+impl ser::SerializeStruct for FieldSerializer<'_> {
+    type Ok = ();
+    type Error = SerError;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SerError> {
+        value.serialize(FieldSerializer { path: join_path(&self.path, key), out: self.out })
+    }
+
+    fn end(self) -> Result<(), SerError> {
+        Ok(())
+    }
+}
+
+
+/// Serializes a configuration struct. Leaf fields are stringified, except for
+/// secret ones.
+#[cfg(feature = "log")]
+struct ConfigSerializer<'a> {
+    path: String,
+    meta: &'static Meta,
+    out: &'a mut Vec<(String, String)>,
+}
+
+#[cfg(feature = "log")]
+impl<'a> Serializer for ConfigSerializer<'a> {
+    type Ok = ();
+    type Error = SerError;
+    type SerializeSeq = Impossible<(), SerError>;
+    type SerializeTuple = Impossible<(), SerError>;
+    type SerializeTupleStruct = Impossible<(), SerError>;
+    type SerializeTupleVariant = Impossible<(), SerError>;
+    type SerializeMap = Impossible<(), SerError>;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Impossible<(), SerError>;
+
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<Self, SerError> {
+        Ok(self)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<(), SerError> {
+        value.serialize(self)
+    }
+
+    unexpected!(
+        "configuration struct";
+        serialize_bool(v: bool) -> (),
+        serialize_i8(v: i8) -> (),
+        serialize_i16(v: i16) -> (),
+        serialize_i32(v: i32) -> (),
+        serialize_i64(v: i64) -> (),
+        serialize_u8(v: u8) -> (),
+        serialize_u16(v: u16) -> (),
+        serialize_u32(v: u32) -> (),
+        serialize_u64(v: u64) -> (),
+        serialize_f32(v: f32) -> (),
+        serialize_f64(v: f64) -> (),
+        serialize_char(v: char) -> (),
+        serialize_str(v: &str) -> (),
+        serialize_bytes(v: &[u8]) -> (),
+        serialize_none() -> (),
+        serialize_unit() -> (),
+        serialize_unit_struct(name: &'static str) -> (),
+        serialize_unit_variant(name: &'static str, index: u32, variant: &'static str) -> (),
+        serialize_seq(len: Option<usize>) -> Self::SerializeSeq,
+        serialize_tuple(len: usize) -> Self::SerializeTuple,
+        serialize_tuple_struct(name: &'static str, len: usize) -> Self::SerializeTupleStruct,
+        serialize_tuple_variant(
+            name: &'static str,
+            index: u32,
+            variant: &'static str,
+            len: usize
+        ) -> Self::SerializeTupleVariant,
+        serialize_map(len: Option<usize>) -> Self::SerializeMap,
+        serialize_struct_variant(
+            name: &'static str,
+            index: u32,
+            variant: &'static str,
+            len: usize
+        ) -> Self::SerializeStructVariant,
+    );
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<(), SerError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: &T,
+    ) -> Result<(), SerError> {
+        self.serialize_unit()
+    }
+}
+
+#[cfg(feature = "log")]
+impl ser::SerializeStruct for ConfigSerializer<'_> {
+    type Ok = ();
+    type Error = SerError;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SerError> {
+        let path = join_path(&self.path, key);
+        let field = self.meta.fields.iter().find(|f| f.name == key);
+        match field.map(|f| &f.kind) {
+            Some(FieldKind::Nested { meta }) => {
+                return value.serialize(ConfigSerializer { path, meta, out: self.out });
+            }
+            Some(FieldKind::Leaf { secret: true, .. }) => {
+                self.out.push((path, REDACTED.into()));
+                return Ok(());
+            }
+            Some(FieldKind::Leaf { .. }) => {}
+
+            // `extend` fields are not part of the meta information, but their
+            // fields are spliced into the parent's. So we try to serialize the
+            // value as part of the parent. If that fails, the key is unknown
+            // (e.g. due to `#[serde(rename)]`) and might belong to a secret
+            // field, so its value is redacted.
+            None => {
+                let mut fields = Vec::new();
+                let parent = ConfigSerializer {
+                    path: self.path.clone(),
+                    meta: self.meta,
+                    out: &mut fields,
+                };
+                if value.serialize(parent).is_ok() {
+                    self.out.extend(fields);
+                } else {
+                    self.out.push((path, REDACTED.into()));
+                }
+                return Ok(());
+            }
+        }
+
+        let mut s = String::new();
+        value.serialize(ValueSerializer { out: &mut s, quote_strings: false })?;
+        self.out.push((path, s));
+        Ok(())
+    }
+
+    fn end(self) -> Result<(), SerError> {
+        Ok(())
+    }
+}
+
+
+/// Serializes a leaf value into a string.
+struct ValueSerializer<'a> {
+    out: &'a mut String,
+
+    /// Strings are only quoted inside of arrays and maps.
+    quote_strings: bool,
+}
+
+impl ValueSerializer<'_> {
+    fn write(self, v: impl fmt::Display) -> Result<(), SerError> {
+        use fmt::Write;
+        write!(self.out, "{v}").map_err(|e| SerError(e.to_string()))
+    }
+
+    fn nested(&mut self) -> ValueSerializer<'_> {
+        ValueSerializer { out: self.out, quote_strings: true }
+    }
+}
+
+macro_rules! serialize_display {
+    ($( $method:ident: $ty:ty ),*) => {
+        $(
+            fn $method(self, v: $ty) -> Result<(), SerError> {
+                self.write(v)
+            }
+        )*
+    };
+}
+
+impl<'a> Serializer for ValueSerializer<'a> {
+    type Ok = ();
+    type Error = SerError;
+    type SerializeSeq = Compound<'a>;
+    type SerializeTuple = Compound<'a>;
+    type SerializeTupleStruct = Compound<'a>;
+    type SerializeTupleVariant = Compound<'a>;
+    type SerializeMap = Compound<'a>;
+    type SerializeStruct = Compound<'a>;
+    type SerializeStructVariant = Compound<'a>;
+
+    serialize_display!(
+        serialize_bool: bool,
+        serialize_i8: i8,
+        serialize_i16: i16,
+        serialize_i32: i32,
+        serialize_i64: i64,
+        serialize_i128: i128,
+        serialize_u8: u8,
+        serialize_u16: u16,
+        serialize_u32: u32,
+        serialize_u64: u64,
+        serialize_u128: u128,
+        serialize_f32: f32,
+        serialize_f64: f64
+    );
+
+    fn serialize_char(self, v: char) -> Result<(), SerError> {
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), SerError> {
+        if self.quote_strings {
+            self.write(format_args!("{v:?}"))
+        } else {
+            self.write(v)
+        }
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), SerError> {
+        self.write(format_args!("{v:?}"))
+    }
+
+    fn serialize_none(self) -> Result<(), SerError> {
+        self.write("null")
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<(), SerError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), SerError> {
+        self.write("null")
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<(), SerError> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+    ) -> Result<(), SerError> {
+        self.write(variant)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<(), SerError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        mut self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), SerError> {
+        self.out.push_str(variant);
+        self.out.push('(');
+        value.serialize(self.nested())?;
+        self.out.push(')');
+        Ok(())
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Compound<'a>, SerError> {
+        Ok(Compound::start(self.out, "", '[', ']'))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Compound<'a>, SerError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _: &'static str,
+        len: usize,
+    ) -> Result<Compound<'a>, SerError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        _: usize,
+    ) -> Result<Compound<'a>, SerError> {
+        Ok(Compound::start(self.out, variant, '(', ')'))
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Compound<'a>, SerError> {
+        Ok(Compound::start(self.out, "", '{', '}'))
+    }
+
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<Compound<'a>, SerError> {
+        self.serialize_map(None)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        _: usize,
+    ) -> Result<Compound<'a>, SerError> {
+        Ok(Compound::start(self.out, variant, '{', '}'))
+    }
+}
+
+/// Writes arrays, maps and similar, separating the elements by `, `.
+struct Compound<'a> {
+    out: &'a mut String,
+    first: bool,
+    close: char,
+}
+
+impl<'a> Compound<'a> {
+    fn start(out: &'a mut String, prefix: &str, open: char, close: char) -> Self {
+        out.push_str(prefix);
+        out.push(open);
+        Self { out, first: true, close }
+    }
+
+    fn element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), SerError> {
+        if !self.first {
+            self.out.push_str(", ");
+        }
+        self.first = false;
+        value.serialize(ValueSerializer { out: self.out, quote_strings: true })
+    }
+
+    fn entry<T: ?Sized + Serialize>(&mut self, key: &str, value: &T) -> Result<(), SerError> {
+        self.element(key)?;
+        self.out.push_str(": ");
+        value.serialize(ValueSerializer { out: self.out, quote_strings: true })
+    }
+
+    fn finish(self) -> Result<(), SerError> {
+        self.out.push(self.close);
+        Ok(())
+    }
+}
+
+macro_rules! impl_compound {
+    ($( $trait:ident :: $method:ident ),*) => {
+        $(
+            impl ser::$trait for Compound<'_> {
+                type Ok = ();
+                type Error = SerError;
+
+                fn $method<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), SerError> {
+                    self.element(value)
+                }
+
+                fn end(self) -> Result<(), SerError> {
+                    self.finish()
+                }
+            }
+        )*
+    };
+}
+
+impl_compound!(
+    SerializeSeq::serialize_element,
+    SerializeTuple::serialize_element,
+    SerializeTupleStruct::serialize_field,
+    SerializeTupleVariant::serialize_field
+);
+
+impl ser::SerializeMap for Compound<'_> {
+    type Ok = ();
+    type Error = SerError;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), SerError> {
+        self.element(key)?;
+        self.out.push_str(": ");
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), SerError> {
+        value.serialize(ValueSerializer { out: self.out, quote_strings: true })
+    }
+
+    fn end(self) -> Result<(), SerError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStruct for Compound<'_> {
+    type Ok = ();
+    type Error = SerError;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SerError> {
+        self.entry(key, value)
+    }
+
+    fn end(self) -> Result<(), SerError> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStructVariant for Compound<'_> {
+    type Ok = ();
+    type Error = SerError;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SerError> {
+        self.entry(key, value)
+    }
+
+    fn end(self) -> Result<(), SerError> {
+        self.finish()
+    }
+}
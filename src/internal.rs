@@ -4,7 +4,11 @@
 
 use std::fmt::Display;
 
-use crate::{error::ErrorInner, Error};
+use crate::{
+    error::ErrorInner,
+    meta::{Expr, Field, FieldKind, LeafKind},
+    Error,
+};
 
 
 pub fn into_deserializer<'de, T>(src: T) -> <T as serde::de::IntoDeserializer<'de>>::Deserializer
@@ -31,6 +35,150 @@ pub fn map_err_prefix_path<T>(res: Result<T, Error>, prefix: &str) -> Result<T,
     })
 }
 
+/// Joins the path of a nested field, e.g. `http.port`.
+pub fn join_path(prefix: &str, name: &str) -> String {
+    if prefix.is_empty() {
+        name.into()
+    } else {
+        format!("{prefix}.{name}")
+    }
+}
+
+/// Implemented by the derive for partial types. The impl has a where clause
+/// requiring `PartialEq` for all field types, so the generated `semantic_eq`
+/// method is only callable if that's the case.
+pub trait SemanticEq {
+    fn semantic_eq_with_defaults(&self, other: &Self, defaults: &Self) -> bool;
+}
+
+pub fn leaf_semantic_eq<T: PartialEq>(a: &Option<T>, b: &Option<T>, default: &Option<T>) -> bool {
+    let is_set = |v: &Option<T>| v.is_some() && v.as_ref() != default.as_ref();
+    match (is_set(a), is_set(b)) {
+        (true, true) => a == b,
+        (a_set, b_set) => a_set == b_set,
+    }
+}
+
+/// Sets a default value overridden by `#[config(extend(...))]`.
+pub fn override_default<'de, T, D>(slot: &mut Option<T>, value: D, msg: &str)
+where
+    T: serde::Deserialize<'de>,
+    D: serde::Deserializer<'de>,
+{
+    *slot = Some(T::deserialize(value).expect(msg));
+}
+
+/// Placeholder to initialize arrays in the `const fn`s below.
+const PLACEHOLDER_FIELD: Field = Field {
+    name: "",
+    doc: &[],
+    hidden_in_template: false,
+    kind: FieldKind::Leaf {
+        env: None,
+        has_parse_env: false,
+        has_deserialize_with: false,
+        example: None,
+        secret: false,
+        kind: LeafKind::Optional,
+    },
+};
+
+/// Concatenates the fields of a struct with `extend` fields. `N` has to be
+/// the total number of fields.
+pub const fn concat_fields<const N: usize>(parts: &[&[Field]]) -> [Field; N] {
+    let mut out = [PLACEHOLDER_FIELD; N];
+    let mut i = 0;
+    let mut p = 0;
+    while p < parts.len() {
+        let mut j = 0;
+        while j < parts[p].len() {
+            out[i] = parts[p][j];
+            i += 1;
+            j += 1;
+        }
+        p += 1;
+    }
+    assert!(i == N, "bug: wrong number of fields");
+    out
+}
+
+/// Returns the `N` fields of an extended struct with the given defaults
+/// overridden.
+pub const fn override_defaults<const N: usize>(
+    fields: &[Field],
+    overrides: &[(&str, Expr)],
+) -> [Field; N] {
+    let mut out = [PLACEHOLDER_FIELD; N];
+    let mut i = 0;
+    while i < N {
+        out[i] = fields[i];
+        i += 1;
+    }
+
+    let mut o = 0;
+    while o < overrides.len() {
+        let (name, default) = overrides[o];
+        let mut found = false;
+        let mut i = 0;
+        while i < N {
+            if str_eq(out[i].name, name) {
+                out[i].kind = match out[i].kind {
+                    FieldKind::Leaf {
+                        env,
+                        has_parse_env,
+                        has_deserialize_with,
+                        example,
+                        secret,
+                        kind: LeafKind::Required { .. },
+                    } => FieldKind::Leaf {
+                        env,
+                        has_parse_env,
+                        has_deserialize_with,
+                        example,
+                        secret,
+                        kind: LeafKind::Required { default: Some(default) },
+                    },
+                    _ => panic!("overridden defaults have to refer to required leaf fields"),
+                };
+                found = true;
+            }
+            i += 1;
+        }
+        assert!(found, "overridden default refers to a non-existent field");
+        o += 1;
+    }
+    out
+}
+
+const fn str_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+pub fn warning(msg: &str) -> crate::Warning {
+    crate::Warning { path: String::new(), msg: msg.into() }
+}
+
+pub fn prefix_warnings(
+    warnings: Vec<crate::Warning>,
+    prefix: &str,
+) -> impl Iterator<Item = crate::Warning> + '_ {
+    warnings.into_iter().map(move |mut w| {
+        w.path = if w.path.is_empty() { prefix.into() } else { format!("{prefix}.{}", w.path) };
+        w
+    })
+}
+
 pub fn validate_field<T, E: Display>(
     t: &T,
     validate: &dyn Fn(&T) -> Result<(), E>,
@@ -84,6 +232,19 @@ pub fn from_env<T>(
     }
 }
 
+/// Like `from_env`, but for `#[config(env_bool_lenient)]` fields: values that
+/// are not recognized as bool are treated as if the env var was not set.
+pub fn from_env_bool_lenient<T>(
+    key: &str,
+    field: &str,
+    deserialize: fn(crate::env::Deserializer) -> Result<T, crate::env::DeError>,
+) -> Result<Option<T>, Error> {
+    match std::env::var(key) {
+        Ok(s) if crate::env::parse_bool(s.trim()).is_none() => Ok(None),
+        _ => from_env(key, field, deserialize),
+    }
+}
+
 pub fn from_env_with_parser<T, E: std::error::Error + Send + Sync + 'static, E2: Display>(
     key: &str,
     field: &str,
@@ -144,3 +305,134 @@ where
         serde::de::value::MapDeserializer::new(self.0.into_iter())
     }
 }
+
+/// Deserializes a `flatten_serde` field. Returns `None` if none of the keys
+/// of `T` are present, so that the field can be filled by a lower priority
+/// layer. This is detected by counting the map entries `T` reads.
+pub fn deserialize_flattened<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: serde::Deserialize<'de>,
+{
+    let num_keys = std::cell::Cell::new(None);
+    let result = T::deserialize(CountingDeserializer { inner: deserializer, num_keys: &num_keys });
+    match num_keys.get() {
+        Some(0) => Ok(None),
+        _ => result.map(Some),
+    }
+}
+
+/// Returns `T` deserialized from an empty map, if that works, e.g. because
+/// all fields have `#[serde(default)]`. Used as default value for
+/// `flatten_serde` fields.
+pub fn flattened_default<T: serde::de::DeserializeOwned>() -> Option<T> {
+    let empty = MapIntoDeserializer::<(), ()>(vec![]);
+    T::deserialize(serde::de::IntoDeserializer::<serde::de::value::Error>::into_deserializer(empty))
+        .ok()
+}
+
+/// Forwards everything to `inner`, but counts the keys read from maps in
+/// `num_keys`, which is `None` if no map was deserialized.
+struct CountingDeserializer<'a, D> {
+    inner: D,
+    num_keys: &'a std::cell::Cell<Option<usize>>,
+}
+
+macro_rules! forward_deserialize {
+    ($wrap:ident: $( $method:ident ( $($arg:ident: $ty:ty),* ) ),* $(,)?) => {
+        $(
+            fn $method<V: serde::de::Visitor<'de>>(
+                self,
+                $($arg: $ty,)*
+                visitor: V,
+            ) -> Result<V::Value, Self::Error> {
+                forward_deserialize!(@visitor $wrap, self, visitor);
+                self.inner.$method($($arg,)* visitor)
+            }
+        )*
+    };
+    (@visitor count, $self:ident, $visitor:ident) => {
+        let $visitor = CountingVisitor { inner: $visitor, num_keys: $self.num_keys };
+    };
+    (@visitor plain, $self:ident, $visitor:ident) => {};
+}
+
+// Only `deserialize_map` and `deserialize_struct` are counted, which is what
+// derived `Deserialize` impls of structs and maps call. For everything else,
+// the result is passed through unchanged.
+impl<'de, D: serde::Deserializer<'de>> serde::Deserializer<'de> for CountingDeserializer<'_, D> {
+    type Error = D::Error;
+
+    forward_deserialize!(count:
+        deserialize_map(),
+        deserialize_struct(name: &'static str, fields: &'static [&'static str]),
+    );
+
+    forward_deserialize!(plain:
+        deserialize_any(), deserialize_bool(), deserialize_i8(), deserialize_i16(),
+        deserialize_i32(), deserialize_i64(), deserialize_i128(), deserialize_u8(),
+        deserialize_u16(), deserialize_u32(), deserialize_u64(), deserialize_u128(),
+        deserialize_f32(), deserialize_f64(), deserialize_char(), deserialize_str(),
+        deserialize_string(), deserialize_bytes(), deserialize_byte_buf(),
+        deserialize_option(), deserialize_unit(), deserialize_seq(),
+        deserialize_identifier(), deserialize_ignored_any(),
+        deserialize_unit_struct(name: &'static str),
+        deserialize_newtype_struct(name: &'static str),
+        deserialize_tuple(len: usize),
+        deserialize_tuple_struct(name: &'static str, len: usize),
+        deserialize_enum(name: &'static str, variants: &'static [&'static str]),
+    );
+
+    fn is_human_readable(&self) -> bool {
+        self.inner.is_human_readable()
+    }
+}
+
+struct CountingVisitor<'a, V> {
+    inner: V,
+    num_keys: &'a std::cell::Cell<Option<usize>>,
+}
+
+impl<'de, V: serde::de::Visitor<'de>> serde::de::Visitor<'de> for CountingVisitor<'_, V> {
+    type Value = V::Value;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.inner.expecting(f)
+    }
+
+    fn visit_map<A: serde::de::MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+        self.num_keys.set(Some(0));
+        self.inner.visit_map(CountingMapAccess { inner: map, num_keys: self.num_keys })
+    }
+}
+
+struct CountingMapAccess<'a, A> {
+    inner: A,
+    num_keys: &'a std::cell::Cell<Option<usize>>,
+}
+
+impl<'de, A: serde::de::MapAccess<'de>> serde::de::MapAccess<'de> for CountingMapAccess<'_, A> {
+    type Error = A::Error;
+
+    fn next_key_seed<K: serde::de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        let key = self.inner.next_key_seed(seed)?;
+        if key.is_some() {
+            self.num_keys.set(self.num_keys.get().map(|n| n + 1));
+        }
+        Ok(key)
+    }
+
+    fn next_value_seed<S: serde::de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: S,
+    ) -> Result<S::Value, Self::Error> {
+        self.inner.next_value_seed(seed)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
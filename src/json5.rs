@@ -6,7 +6,7 @@ use std::fmt::{self, Write};
 use crate::{
     Config,
     template::{self, Formatter},
-    meta::Expr,
+    meta::{Expr, Preambles},
 };
 
 
@@ -99,6 +99,18 @@ pub fn template<C: Config>(options: FormatOptions) -> String {
     out.finish()
 }
 
+/// Like [`template`] with default options, but with all default values
+/// emitted as actual values instead of commented-out. Used by
+/// [`crate::test::roundtrip_all_formats`].
+#[cfg(feature = "test-util")]
+pub(crate) fn template_with_defaults<C: Config>() -> String {
+    let options = FormatOptions::default();
+    let lookup = template::default_lookup::<PrintExpr>(&C::META);
+    let mut out = Json5Formatter::new(&options);
+    template::format_with_values(&C::META, &mut out, options.general, Some(&lookup));
+    out.finish()
+}
+
 struct Json5Formatter {
     indent: u8,
     buffer: String,
@@ -130,6 +142,10 @@ impl Json5Formatter {
 impl Formatter for Json5Formatter {
     type ExprPrinter = PrintExpr;
 
+    fn preamble(&self, preambles: &Preambles) -> Option<&'static str> {
+        preambles.json5
+    }
+
     fn buffer(&mut self) -> &mut String {
         &mut self.buffer
     }
@@ -139,17 +155,23 @@ impl Formatter for Json5Formatter {
         writeln!(self.buffer, "//{comment}").unwrap();
     }
 
-    fn disabled_field(&mut self, name: &str, value: Option<&'static Expr>) {
+    fn disabled_field(&mut self, name: &'static str, value: Option<&'static Expr>) {
+        let name = PrintKey(name);
         match value.map(PrintExpr) {
             None => self.comment(format_args!("{name}: ,")),
             Some(v) => self.comment(format_args!("{name}: {v},")),
         };
     }
 
+    fn enabled_field(&mut self, name: &'static str, value: &str) {
+        self.emit_indentation();
+        writeln!(self.buffer, "{}: {value},", PrintKey(name)).unwrap();
+    }
+
     fn start_nested(&mut self, name: &'static str, doc: &[&'static str]) {
         doc.iter().for_each(|doc| self.comment(doc));
         self.emit_indentation();
-        writeln!(self.buffer, "{name}: {{").unwrap();
+        writeln!(self.buffer, "{}: {{", PrintKey(name)).unwrap();
         self.depth += 1;
     }
 
@@ -192,6 +214,26 @@ impl fmt::Display for PrintExpr {
     }
 }
 
+/// Helper to emit a key into JSON5, quoting it if it's not a valid identifier.
+struct PrintKey(&'static str);
+
+impl fmt::Display for PrintKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut chars = self.0.chars();
+        let is_ident_start = |c: char| c.is_alphabetic() || c == '_' || c == '$';
+        let is_valid_ident = chars.next().map_or(false, is_ident_start)
+            && chars.all(|c| is_ident_start(c) || c.is_alphanumeric());
+
+        if is_valid_ident {
+            f.write_str(self.0)
+        } else {
+            json5::to_string(&self.0)
+                .expect("string serialization to JSON5 failed")
+                .fmt(f)
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::{template, FormatOptions};
@@ -212,9 +254,38 @@ mod tests {
         assert_str_eq!(&out, include_format_output!("1-no-comments.json5"));
     }
 
+    #[test]
+    fn version_key() {
+        let mut options = FormatOptions::default();
+        options.general.version_key = Some(("config_version", 3));
+        options.general.comments = false;
+        let out = template::<test_utils::example1::Conf>(options);
+        assert!(out.starts_with("{\n  config_version: 3,\n\n  //site_name: ,\n"), "{out}");
+    }
+
     #[test]
     fn immediately_nested() {
         let out = template::<test_utils::example2::Conf>(Default::default());
         assert_str_eq!(&out, include_format_output!("2-default.json5"));
     }
+
+    #[test]
+    fn special_keys() {
+        let out = template::<test_utils::example3::Conf>(Default::default());
+        assert_str_eq!(&out, include_format_output!("3-default.json5"));
+    }
+
+    #[test]
+    fn preamble() {
+        let out = template::<test_utils::example4::Conf>(FormatOptions::default());
+        assert!(out.starts_with(concat!(
+            "// json5 preamble\n",
+            "\n",
+            "// Config with format-specific preambles.\n",
+        )), "{out}");
+
+        // Types without preambles are unaffected.
+        let out = template::<test_utils::example1::Conf>(FormatOptions::default());
+        assert_str_eq!(&out, include_format_output!("1-default.json5"));
+    }
 }
@@ -139,10 +139,9 @@
 //! let from_file: PartialConf = File::with_format("/etc/foo/config", FileFormat::Toml)
 //!     .required()
 //!     .load()?;
-//! let manual = PartialConf {
-//!     // Remember: all fields in the partial types are `Option`s!
-//!     foo: Some(3.14),
-//! };
+//! // Remember: all fields in the partial types are `Option`s! Instead of a
+//! // struct literal, you can also use the generated setters.
+//! let manual = PartialConf::empty().set_foo(3.14);
 //! let defaults = PartialConf::default_values();
 //!
 //! let merged = from_file.with_fallback(manual).with_fallback(defaults);
@@ -172,6 +171,10 @@
 //! - `toml`: enables TOML support and adds the `toml` dependency.
 //! - `yaml`: enables YAML support and adds the `serde_yaml` dependency.
 //! - `json5`: enables JSON5 support and adds the `json5` dependency.
+//! - `log`: adds [`Config::log_effective`] and the `log` dependency.
+//! - `json`: adds [`meta::to_json`] and the `serde_json` dependency.
+//! - `test-util`: adds the [`test`] module with helpers to check your
+//!   configuration types in your test suite.
 
 use serde::Deserialize;
 
@@ -179,9 +182,16 @@ use serde::Deserialize;
 pub mod internal;
 
 mod builder;
+pub mod de;
 pub mod env;
 mod error;
+mod flatten;
 pub mod meta;
+mod values;
+mod warning;
+
+#[cfg(feature = "test-util")]
+pub mod test;
 
 #[cfg(any(feature = "toml", feature = "yaml", feature = "json5"))]
 mod file;
@@ -206,6 +216,8 @@ pub use serde;
 pub use self::{
     builder::Builder,
     error::Error,
+    flatten::flatten_partial,
+    warning::Warning,
 };
 
 #[cfg(any(feature = "toml", feature = "yaml", feature = "json5"))]
@@ -223,6 +235,12 @@ pub use crate::{
 /// case); if you get weird errors "symbol not found", just move the struct
 /// definition outside of the function.
 ///
+/// Generic structs are supported as well. The generic parameters and where
+/// clauses are carried over to the generated impls and partial type. For
+/// leaf fields whose type depends on a generic parameter, the bound
+/// `T: serde::de::DeserializeOwned` is added (unless `deserialize_with` is
+/// used); for nested fields, `T: Config` is added.
+///
 /// # Quick example
 ///
 /// ```
@@ -269,12 +287,24 @@ pub use crate::{
 /// - **Nested fields**: they have to be annotated with `#[config(nested)]` and
 ///   contain a nested configuration object. The type of this field must
 ///   implement `Config`. As implied by the previous statement, `Option<_>` as
-///   type for nested fields is not allowed.
+///   type for nested fields is not allowed. A nested section is thus always
+///   present: values not specified by any layer are filled from the nested
+///   type's default values, just like for leaf fields. So there is no need
+///   for (and no support of) a `default` attribute on nested fields.
 ///
 /// - **Leaf fields**: all fields *not* annotated with `#[config(nested)]`,
 ///   these contain your actual values. The type of such a field has to
 ///   implement `serde::Deserialize` or you have to add a `deserialize_with`
-///   attribute.
+///   attribute. Since configuration is loaded into owned values, the type
+///   cannot borrow data: `&'a str` or `Cow<'a, str>` result in a compile
+///   error, use `String` instead.
+///
+///   ```compile_fail
+///   #[derive(confique::Config)]
+///   struct Conf<'a> {
+///       name: &'a str,
+///   }
+///   ```
 ///
 /// Doc comments on the struct and the individual fields are interpreted and
 /// stored in [`Meta`][meta::Meta]. They are used in the formatting functions
@@ -291,6 +321,15 @@ pub use crate::{
 ///   configuration, no value has been set for them. Optional fields cannot have
 ///   a `#[config(default = ...)]` attribute as that would not make sense.
 ///
+///   For collections, note that "absent" and "empty" are different. A field
+///   `x: Option<Vec<T>>` can end up in three states: `None` if no layer
+///   specifies it, `Some(vec![])` if the highest priority layer that specifies
+///   it sets it to an empty list (e.g. `x = []` in a TOML file), and
+///   `Some(vec![...])` otherwise. An empty list in a higher priority layer is
+///   *not* filled from lower priority layers, as it is a value, not a gap. If
+///   you don't need to distinguish "absent" from "empty", rather use
+///   `Vec<T>` with `#[config(default = [])]`.
+///
 ///
 /// ## Field Attributes
 ///
@@ -308,22 +347,31 @@ pub use crate::{
 /// higher-priority. Currently, the following expressions are allowed:
 ///
 /// - Booleans, e.g. `default = true`
-/// - Integers, e.g. `default = 900`
-/// - Floats, e.g. `default = 3.14`
+/// - Integers, e.g. `default = 900` or `default = -1`
+/// - Floats, e.g. `default = 3.14`, `default = -0.5` or `default = 1e-9`
 /// - Strings, e.g. `default = "fox"`
 /// - Arrays, e.g. `default = ["foo", "bar"]`
 /// - Key value maps, e.g. `default = { "cat": 3.14, "bear": 9.0 }`
 ///
 /// Map keys can be Booleans, integers, floats, and strings. For array and map
 /// values, you can use any of the expressions in the list above (i.e. you
-/// can nest arrays/maps).
+/// can nest arrays/maps). Map defaults are stored in [`Config::META`] and
+/// shown in templates in source order. That's not the case for the loaded
+/// value though: a `HashMap` field iterates in random order. Use `BTreeMap`
+/// or a map type preserving insertion order (like `indexmap::IndexMap`) if
+/// order matters to you.
 ///
 /// The field value is deserialized from the specified default value
 /// (via `serde::de::IntoDeserializer`). So the expression after `default =`
 /// is often not the same Rust type as your field. For example, you can have
 /// `#[config(default = "/foo/bar")]` on the field `path: PathBuf`. This
 /// works fine as `PathBuf` can be deserialized from a string. (Also see the
-/// `IpAddr` field in the example above.)
+/// `IpAddr` field in the example above.) Similarly, unit variants of enums
+/// deriving `Deserialize` can be specified by their (possibly renamed) string
+/// form, e.g. `#[config(default = "info")]` for a field `level: LogLevel`.
+/// Templates show the default in that string form as well. As optional fields
+/// cannot have defaults, use `LogLevel` instead of `Option<LogLevel>` in that
+/// case.
 ///
 /// If you use an integer or float literal without type suffix, `confique` has
 /// to infer the exact type from the type of the field. This should work in
@@ -333,6 +381,81 @@ pub use crate::{
 /// for floats (like Rust does). If that causes problems for you, just add a
 /// type suffix, e.g. `default = 800u32`.
 ///
+/// If the field is a collection (`Vec<_>`, arrays, maps, ...), all suffixes
+/// within an array (or the keys or values of a map) have to be the same, as
+/// the elements of a collection have a single type. Like in Rust, elements
+/// without suffix get the type of their siblings with suffix, e.g.
+/// `default = [1u8, 2]`. Conflicting suffixes for collections result in a
+/// compile error:
+///
+/// ```compile_fail
+/// #[derive(confique::Config)]
+/// struct Conf {
+///     #[config(default = [1u8, 2u16])]
+///     values: Vec<u16>,
+/// }
+/// ```
+///
+/// Instead of a literal, you can also refer to a constant with
+/// `default = const PATH`, e.g. `#[config(default = const DEFAULT_PORT)]` or
+/// `#[config(default = const Self::MAX_SIZE)]`. As the default value is also
+/// stored in [`Config::META`] (e.g. to show it in templates), this is
+/// restricted to fields of integer, float, `bool`, `String` or `&str` type.
+/// The constant has to have exactly the type of the field (`&'static str` for
+/// `String` fields), and integer and float types have to be written as plain
+/// name (e.g. `u16`, not `std::primitive::u16`). Constants cannot be used
+/// inside arrays or maps.
+///
+/// ```compile_fail
+/// const DEFAULT_DIR: &str = "/tmp";
+///
+/// #[derive(confique::Config)]
+/// struct Conf {
+///     #[config(default = const DEFAULT_DIR)]
+///     dir: std::path::PathBuf,
+/// }
+/// ```
+///
+/// ### `example`
+///
+/// ```ignore
+/// #[config(example = <expr>)]
+/// ```
+///
+/// Specifies an example value for a leaf field, using the same expression
+/// syntax as `default` (except `const`). Unlike default values, examples are
+/// never used when loading the configuration, so they can also be specified
+/// for optional fields or in addition to a default value. They are stored in
+/// [`Config::META`] (see `example` in [`meta::FieldKind::Leaf`]), e.g. for
+/// tools generating documentation or example files.
+///
+/// ```
+/// use confique::{Config, meta::{Expr, FieldKind}};
+///
+/// #[derive(Config)]
+/// struct Conf {
+///     #[config(example = "postgres://localhost/app")]
+///     database_url: Option<String>,
+/// }
+///
+/// let field = Conf::META.find("database_url").unwrap();
+/// assert!(matches!(
+///     field.kind,
+///     FieldKind::Leaf { example: Some(Expr::Str("postgres://localhost/app")), .. },
+/// ));
+/// ```
+///
+/// ### `secret`
+///
+/// ```ignore
+/// #[config(secret)]
+/// ```
+///
+/// Marks a leaf field as secret, e.g. a password or an API token. Its value
+/// is replaced by `***` in [`Config::log_effective`]. This is stored as
+/// `secret` in [`meta::FieldKind::Leaf`], so your own tools can also respect
+/// it. Loading the field is not affected.
+///
 /// ### `env`
 ///
 /// ```ignore
@@ -345,6 +468,101 @@ pub use crate::{
 /// If the env var is set to an empty string and if the field fails to
 /// parse/deserialize/validate, it is treated as unset.
 ///
+/// Collections are an exception: an empty string deserializes into an empty
+/// collection (e.g. `Some(vec![])`), which is a value and not filled from
+/// lower priority layers or defaults. This also applies to the
+/// `env::parse::list_by_*` functions. To treat empty strings as unset
+/// instead, use a custom `parse_env` function returning an error for them.
+///
+/// Enums can be loaded from env vars if all their variants are unit or
+/// newtype variants: unit variants are specified by name (e.g. `Debug`),
+/// newtype variants as `Variant:value` (e.g. `Custom:3`), with the value
+/// being deserialized like any other env value. Tuple and struct variants are
+/// not supported and produce an error saying so. Note that `default` values
+/// can only specify unit variants.
+///
+/// ### `env_auto`
+///
+/// ```ignore
+/// #[config(env_auto)]
+/// ```
+///
+/// Loads this field from an environment variable whose key is derived from
+/// the field's path: the prefix set via the struct attribute `env_prefix`,
+/// followed by the names of all nested fields leading to this field and the
+/// field name itself, in `SCREAMING_SNAKE_CASE` and separated by `_`. For
+/// example, with `#[config(env_prefix = "APP_")]` on the root struct, the
+/// field `port` in the nested field `http` is loaded from `APP_HTTP_PORT`.
+///
+/// Can be combined with `env`, which is useful when migrating from
+/// hand-named env vars to a systematic scheme. The explicit `env` key is
+/// checked first; only if it's not set (or set to an empty string treated as
+/// unset, see above) is the derived key checked:
+///
+/// ```
+/// use confique::Config;
+///
+/// #[derive(Config)]
+/// #[config(env_prefix = "APP_")]
+/// struct Conf {
+///     // Checks `LEGACY_LOG_LEVEL` first, then `APP_LOG_LEVEL`.
+///     #[config(env = "LEGACY_LOG_LEVEL", env_auto)]
+///     log_level: Option<String>,
+/// }
+/// # fn main() {}
+/// ```
+///
+/// Templates only mention the explicit `env` key.
+///
+/// ### `env_prefix` (on nested fields)
+///
+/// ```ignore
+/// #[config(nested, env_prefix = "PRIMARY_")]
+/// ```
+///
+/// Replaces the segment that nested fields add to the prefix of `env_auto`
+/// keys, which is the field name followed by `_` by default. This is
+/// necessary if the same configuration type is used for multiple nested
+/// fields whose names don't make good env keys, and useful to shorten keys.
+/// The parent's prefix is still prepended. Explicit `env` keys are not
+/// affected, so types used multiple times should use `env_auto` instead.
+///
+/// ```
+/// use confique::Config;
+///
+/// #[derive(Config)]
+/// #[config(env_prefix = "APP_")]
+/// struct Conf {
+///     // `host` is loaded from `APP_DB_HOST`.
+///     #[config(nested, env_prefix = "DB_")]
+///     primary_database: Endpoint,
+///
+///     // `host` is loaded from `APP_CACHE_HOST`.
+///     #[config(nested, env_prefix = "CACHE_")]
+///     cache: Endpoint,
+/// }
+///
+/// #[derive(Config)]
+/// struct Endpoint {
+///     #[config(env_auto)]
+///     host: String,
+/// }
+/// # fn main() {}
+/// ```
+///
+/// ### `env_bool_lenient`
+///
+/// ```ignore
+/// #[config(env_bool_lenient)]
+/// ```
+///
+/// Only for fields of type `bool` or `Option<bool>` loaded from an
+/// environment variable (via `env` or `env_auto`). By default, a value other
+/// than `1`, `true`, `yes`, `0`, `false` or `no` (case insensitive) results in
+/// an error. With this attribute, such values are treated as if the variable
+/// was not set, i.e. the value is taken from lower priority layers or the
+/// default. Cannot be combined with `parse_env`.
+///
 /// ### `parse_env`
 ///
 /// ```ignore
@@ -354,7 +572,8 @@ pub use crate::{
 /// Function used to parse environment variables. Mostly useful if you need to
 /// parse lists or other complex objects from env vars. Function needs
 /// signature `fn(&str) -> Result<T, impl std::error::Error>` where `T` is the
-/// type of the field. Can only be present if the `env` attribute is present.
+/// type of the field. Can only be present if the `env` or `env_auto`
+/// attribute is present.
 /// Also see [`env::parse`].
 ///
 /// #### `deserialize_with`
@@ -367,6 +586,12 @@ pub use crate::{
 ///
 /// [serde-deser]: https://serde.rs/field-attrs.html#deserialize_with
 ///
+/// The [`de`] module contains a few useful functions for this. For example,
+/// `std::time::Duration` is deserialized from `[secs, nanos]` by `serde`,
+/// meaning that `default = 30` does not work. Using
+/// `deserialize_with = confique::de::duration_seconds` makes it accept
+/// (fractional) seconds instead.
+///
 /// #### `validate`
 ///
 /// ```ignore
@@ -379,6 +604,10 @@ pub use crate::{
 /// load the configuration. The validator is called as part of the
 /// deserialization, and is thus executed for all layers, not just for the
 /// merged configuration.
+/// This includes the default value: an invalid default value results in a
+/// panic when [`Partial::default_values`] is called. To catch that in your
+/// test suite, call [`test::assert_defaults_valid`] (`test-util` feature) in
+/// a test.
 ///
 /// > *Note*: remember ["Parse, don't validate"][parse-not-validate]! If you can
 ///    reasonably represent your validation logic as a type, you should use
@@ -444,6 +673,254 @@ pub use crate::{
 /// }
 /// ```
 ///
+/// Both forms can also be used on `nested` and `with_partial` fields, e.g.
+/// when the same nested type needs different constraints in different
+/// parents. Unlike for leaf fields, the validator is called in
+/// `Config::from_partial` of the parent with the fully built value (after
+/// the nested type's own validation), and a failure is reported like a
+/// failed struct validation of `Parent::field`.
+///
+/// ```
+/// use confique::Config;
+///
+/// #[derive(Config)]
+/// struct Conf {
+///     #[config(nested, validate(replica.pool_size <= 4, "replica pools are limited to 4"))]
+///     replica: DbConf,
+/// }
+///
+/// #[derive(Config)]
+/// struct DbConf {
+///     #[config(default = 8)]
+///     pool_size: u32,
+/// }
+///
+/// # fn main() {
+/// let err = Conf::builder().load().err().unwrap();
+/// assert_eq!(
+///     err.to_string(),
+///     "config validation of `Conf::replica` failed: replica pools are limited to 4",
+/// );
+/// # }
+/// ```
+///
+/// ### `required_if`
+///
+/// ```ignore
+/// #[config(required_if(<expr>))]
+/// ```
+///
+/// Makes an optional field (type `Option<_>`) required under a condition. The
+/// expression is evaluated in `Config::from_partial` (i.e. after all layers
+/// have been merged) and can access all fields of the struct by reference via
+/// their names. If it evaluates to `true` and the field has no value, a
+/// "missing value" error is returned, exactly like for a missing required
+/// field. The check runs before the struct's `validate` function. Example:
+///
+/// ```
+/// use std::path::PathBuf;
+/// use confique::Config;
+///
+/// #[derive(Config)]
+/// struct Conf {
+///     #[config(default = false)]
+///     tls_enabled: bool,
+///
+///     #[config(required_if(*tls_enabled))]
+///     tls_cert: Option<PathBuf>,
+/// }
+/// # fn main() {}
+/// ```
+///
+/// This is just a more convenient and self-documenting alternative to
+/// checking the same in a struct validation function.
+///
+/// ### `no_template`
+///
+/// ```ignore
+/// #[config(no_template)]
+/// ```
+///
+/// Excludes this field from generated configuration templates (e.g.
+/// `toml::template`). Useful for internal tuning knobs that you don't want to
+/// advertise to your users. The field can still be loaded as usual. Can also
+/// be used on nested fields, which excludes the whole section. This is stored
+/// as `hidden_in_template` in [`meta::Field`].
+///
+/// ### `with_partial`
+///
+/// ```ignore
+/// #[config(with_partial = PartialType)]
+/// ```
+///
+/// *Expert feature*: lets a field whose type does not implement `Config`
+/// (e.g. a type from another crate) take part in the layered loading. The
+/// field is treated like a nested configuration with `PartialType` as its
+/// partial type: values from all layers are merged on the level of
+/// `PartialType`, which is only converted into the field type at the very
+/// end, in `Config::from_partial`. `PartialType` has to implement
+/// [`FieldPartial`] with the field type as `Value`. In templates, the field
+/// is shown like a nested configuration described by `FieldPartial::META`.
+///
+/// The easiest way is to use the partial type of a derived configuration
+/// describing the values the field type is built from:
+///
+/// ```
+/// use std::time::Duration;
+/// use confique::{meta::Meta, Config, Error, FieldPartial};
+///
+/// #[derive(Config)]
+/// struct Conf {
+///     #[config(with_partial = PartialTimeout)]
+///     timeout: Duration,
+/// }
+///
+/// #[derive(Config)]
+/// pub struct Timeout {
+///     #[config(default = 30)]
+///     secs: u64,
+///     #[config(default = 0)]
+///     millis: u32,
+/// }
+///
+/// type PartialTimeout = <Timeout as Config>::Partial;
+///
+/// impl FieldPartial for PartialTimeout {
+///     type Value = Duration;
+///     const META: Meta = Timeout::META;
+///
+///     fn into_value(self) -> Result<Duration, Error> {
+///         let t = Timeout::from_partial(self)?;
+///         Ok(Duration::from_secs(t.secs) + Duration::from_millis(t.millis.into()))
+///     }
+/// }
+/// # fn main() {}
+/// ```
+///
+/// Cannot be combined with any other attribute except `no_template`.
+///
+/// ### `extend`
+///
+/// ```ignore
+/// #[config(extend)]
+/// #[config(extend(port = 8080, host = "localhost"))]
+/// ```
+///
+/// Flattens all fields of another configuration type into this struct,
+/// e.g. to share a "base" configuration between multiple services. Unlike
+/// with `nested`, the fields of the base are specified without the field
+/// name: in files, they are on the same level as the other fields of this
+/// struct, `env_auto` keys are derived without an additional segment (unless
+/// `env_prefix` is set on the field) and in [`Config::META`], templates and
+/// error messages, they appear like fields of this struct.
+///
+/// Defaults are taken from the base type, but can be overridden by listing
+/// them in parentheses, with the same syntax for values as `default`. Only
+/// top-level leaf fields of the base can be overridden, and the base type is
+/// unaffected. As with `default`, integer and float types of overridden
+/// defaults cannot always be inferred for the metadata, so add a type suffix
+/// if the exact type matters to you.
+///
+/// ```
+/// use confique::Config;
+///
+/// #[derive(Config)]
+/// struct Base {
+///     #[config(default = 80)]
+///     port: u16,
+///     host: String,
+/// }
+///
+/// #[derive(Config)]
+/// struct Service {
+///     // `port` defaults to 8080 here, while `Base` still uses 80.
+///     #[config(extend(port = 8080))]
+///     base: Base,
+///     name: String,
+/// }
+///
+/// # fn main() {
+/// let names = Service::META.fields.iter().map(|f| f.name).collect::<Vec<_>>();
+/// assert_eq!(names, ["port", "host", "name"]);
+/// # }
+/// ```
+///
+/// Limitations:
+/// - It's implemented via `#[serde(flatten)]`, which does not support
+///   `deny_unknown_fields`, neither on this struct nor on the base type.
+///   Sources that provide all values as strings (`lookup_fn`, `overrides`
+///   and `dir_as_keys` of [`Builder`]) cannot set fields of the base and
+///   return an error if they contain such a value. Files and env vars are
+///   not affected.
+/// - Field names of the base must not clash with other fields of this struct.
+/// - Generic structs cannot have `extend` fields.
+/// - To override defaults, the fields of the base's partial type have to be
+///   visible to this struct, which they are if both are defined in the same
+///   module or if the base type is `pub` or `pub(crate)`.
+///
+/// ### `flatten_serde`
+///
+/// ```ignore
+/// #[config(flatten_serde)]
+/// ```
+///
+/// Embeds a type that implements `Deserialize`, but not `Config` (e.g. the
+/// settings struct of a library), with its keys flattened into this struct,
+/// like `#[serde(flatten)]`. The field type can be `T` or `Option<T>`.
+///
+/// ```
+/// use confique::Config;
+/// # mod some_lib { #[derive(serde::Deserialize)] pub struct PoolSettings { pub size: u32 } }
+///
+/// #[derive(Config)]
+/// struct Conf {
+///     #[config(default = 8080)]
+///     port: u16,
+///
+///     // Loads `size = 4` from the same table as `port`.
+///     #[config(flatten_serde)]
+///     pool: some_lib::PoolSettings,
+/// }
+/// # fn main() {}
+/// ```
+///
+/// Unlike with `#[config(nested)]`, the field is all-or-nothing: it is
+/// deserialized from a single layer (the one with the highest priority that
+/// contains any of its keys) and individual keys are not filled from other
+/// layers. A layer containing none of its keys leaves the field unset. If
+/// the type can be deserialized from no keys at all (e.g. because all its
+/// fields have `#[serde(default)]`), that is used as default value.
+/// Furthermore, the field cannot be loaded from environment variables and is
+/// not shown in templates, as its keys are unknown to confique. Cannot be
+/// combined with `default`, `env`, `env_auto`, `parse_env`,
+/// `deserialize_with` and `validate`, nor used in structs with
+/// `deny_unknown_fields`.
+///
+/// ### Attribute combinations
+///
+/// Not all field attributes can be combined. This table lists which ones are
+/// allowed for the different kinds of fields. All others result in a compile
+/// error.
+///
+/// | Field kind | Allowed attributes |
+/// | --- | --- |
+/// | Leaf, `Option<_>` | `env`, `env_auto`, `parse_env`¹, `deserialize_with`, `validate`, `required_if`, `example`, `secret`, `no_template`, `env_bool_lenient`¹ ² |
+/// | Leaf, other types | like `Option<_>`, but `default` instead of `required_if` |
+/// | `nested` | `env_prefix`, `no_template`, `validate` |
+/// | `with_partial` | `env_prefix`, `no_template`, `validate` |
+/// | `extend` | `env_prefix` |
+/// | `flatten_serde` | `no_template`, `required_if` (if the type is `Option<_>`) |
+///
+/// ¹ Requires `env` or `env_auto`. ² Only for `bool` and `Option<bool>`, and
+/// not together with `parse_env`.
+///
+/// `nested`, `with_partial` and `extend` are mutually exclusive and cannot be
+/// used with `Option<_>` types. Leaf fields of nested configurations can of
+/// course still be loaded from env variables: `env` goes on the field inside
+/// the nested struct. `extend` and `flatten_serde` fields cannot be used in
+/// structs with `deny_unknown_fields`. Each attribute can only be specified
+/// once per field.
+///
 ///
 /// ## Struct attributes
 ///
@@ -484,6 +961,33 @@ pub use crate::{
 /// # fn main() {}
 /// ```
 ///
+/// ### `warn`
+///
+/// ```ignore
+/// #[config(warn(<expr>, "msg"))]
+/// ```
+///
+/// Adds a non-fatal check: if `<expr>` evaluates to `true` for the loaded
+/// configuration, a [`Warning`] with the given message is reported by
+/// [`Config::warnings`] and [`Builder::load_with_warnings`], but loading
+/// does not fail. Like with `required_if`, the expression can access all
+/// fields of the struct by reference. Can be specified multiple times.
+/// Warnings of nested configurations are included, with their path.
+///
+/// ```
+/// use confique::Config;
+///
+/// #[derive(Config)]
+/// #[config(warn(*port < 1024, "ports below 1024 usually require root"))]
+/// struct Conf {
+///     port: u16,
+/// }
+/// # fn main() {}
+/// ```
+///
+/// Warnings are only checked on the struct level; fields cannot have `warn`
+/// attributes.
+///
 /// ### `partial_attr`
 ///
 /// ```ignore
@@ -494,6 +998,91 @@ pub use crate::{
 /// For example, `#[config(partial_attr(derive(Clone)))]` can be used to make
 /// the partial type implement `Clone`.
 ///
+/// This includes serde container attributes, as the partial type derives
+/// `Deserialize`. For example, `partial_attr(serde(from = "Raw"))` lets you
+/// customize how a whole layer is deserialized, e.g. to also accept a single
+/// string for a nested section:
+///
+/// ```
+/// use confique::Config;
+///
+/// #[derive(serde::Deserialize)]
+/// #[serde(untagged)]
+/// enum RawServer {
+///     Host(String),
+///     Table { host: Option<String>, port: Option<u16> },
+/// }
+///
+/// #[derive(Config)]
+/// #[config(partial_attr(serde(from = "RawServer")))]
+/// struct Server {
+///     host: String,
+///     #[config(default = 443)]
+///     port: u16,
+/// }
+///
+/// impl From<RawServer> for <Server as Config>::Partial {
+///     fn from(raw: RawServer) -> Self {
+///         match raw {
+///             RawServer::Host(host) => Self { host: Some(host), port: None },
+///             RawServer::Table { host, port } => Self { host, port },
+///         }
+///     }
+/// }
+/// # fn main() {}
+/// ```
+///
+/// Keep in mind that confique itself does not know about these attributes:
+/// [`Config::META`], templates and errors still use the Rust field names.
+/// So attributes changing the expected keys (like `rename_all`) are only
+/// reflected when deserializing the partial type, e.g. from files, but not
+/// in string-based sources like [`Builder::overrides`].
+///
+/// ### `deny_unknown_fields`
+///
+/// ```ignore
+/// #[config(deny_unknown_fields)]
+/// ```
+///
+/// Makes deserialization of the partial type fail if the input contains keys
+/// that don't correspond to any field of this struct. This is equivalent to
+/// `#[config(partial_attr(serde(deny_unknown_fields)))]`. As all fields of
+/// the partial type are optional, layers that only specify some of the
+/// values (or none of them) are still accepted.
+///
+/// This only concerns the keys directly inside this struct: the attribute is
+/// not inherited by nested configurations, and nested configurations with
+/// this attribute reject unknown keys in their section, regardless of
+/// whether the parent has the attribute. As the partial type of a nested
+/// configuration is generated by its own derive, the attribute has to be put
+/// on the nested struct; it cannot be specified per nested field.
+///
+/// ### `env_prefix`
+///
+/// ```ignore
+/// #[config(env_prefix = "APP_")]
+/// ```
+///
+/// Sets the prefix of the environment variable keys derived for fields with
+/// `#[config(env_auto)]` (see above). Only has an effect when the struct is
+/// the root of the configuration (i.e. when its partial type's `from_env` is
+/// called directly): nested configurations use the prefix of their parent
+/// instead (see also `env_prefix` on nested fields above). Default: no
+/// prefix.
+///
+/// ### `toml_preamble`, `yaml_preamble`, `json5_preamble`
+///
+/// ```ignore
+/// #[config(toml_preamble = "#:schema ./schema.json")]
+/// #[config(yaml_preamble = "# yaml-language-server: $schema=./schema.json")]
+/// ```
+///
+/// Text that is emitted verbatim at the very top of templates of the
+/// respective format, followed by an empty line. This is useful for
+/// directives of editor tooling, e.g. to associate a JSON schema with the
+/// file. As the text is not modified, it has to be valid in the target format
+/// (usually a comment). Only the preambles of the root configuration are used.
+///
 ///
 /// # What the macro generates
 ///
@@ -521,6 +1110,17 @@ pub use crate::{
 ///     }
 ///
 ///     impl confique::Partial for PartialConf { ... }
+///
+///     // Chainable setters, e.g. `PartialConf::empty().set_color("red")`.
+///     impl PartialConf {
+///         pub(super) fn set_color(mut self, value: impl Into<String>) -> Self { ... }
+///         pub(super) fn set_http(mut self, value: <HttpConf as confique::Config>::Partial) -> Self { ... }
+///     }
+///
+///     // Only if all field types implement `PartialEq`.
+///     impl PartialConf {
+///         pub(super) fn semantic_eq(&self, other: &Self) -> bool { ... }
+///     }
 /// }
 ///
 /// // ----- Generated for `HttpConf` -----
@@ -539,6 +1139,35 @@ pub use crate::{
 ///     impl confique::Partial for PartialHttpConf { ... }
 /// }
 /// ```
+///
+/// `semantic_eq` compares two partial configuration objects while treating
+/// values equal to their default value (see
+/// [`default_values`][Partial::default_values]) as unset. So unlike
+/// `PartialEq`, "not set" and "set to the default value" are considered
+/// equal. Nested configurations are compared the same way. This is useful
+/// to, for example, check whether a configuration file changes anything
+/// compared to the defaults (by comparing against
+/// [`empty`][Partial::empty]), or to only show settings that differ from the
+/// defaults.
+///
+/// ```
+/// use confique::{Config, Partial};
+///
+/// #[derive(Config)]
+/// struct Conf {
+///     #[config(default = 8080)]
+///     port: u16,
+/// }
+///
+/// type PartialConf = <Conf as Config>::Partial;
+///
+/// let mut user = PartialConf::empty();
+/// user.port = Some(8080);
+/// assert!(user.semantic_eq(&PartialConf::empty()));
+///
+/// user.port = Some(9000);
+/// assert!(!user.semantic_eq(&PartialConf::empty()));
+/// ```
 pub use confique_macro::Config;
 
 
@@ -579,6 +1208,14 @@ pub trait Config: Sized {
     /// - the struct validation fails (see `validate` attribute on derive macro)
     fn from_partial(partial: Self::Partial) -> Result<Self, Error>;
 
+    /// Returns the warnings of all `#[config(warn(...))]` attributes of this
+    /// and all nested configurations whose condition holds. Usually called via
+    /// [`Builder::load_with_warnings`]. The default implementation returns no
+    /// warnings.
+    fn warnings(&self) -> Vec<Warning> {
+        Vec::new()
+    }
+
     /// Convenience builder to configure, load and merge multiple configuration
     /// sources. **Sources specified earlier have a higher priority**; later
     /// sources only fill in the gaps. After all sources have been loaded, the
@@ -645,6 +1282,90 @@ pub trait Config: Sized {
 
         Self::from_partial(file.load::<Self::Partial>()?.with_fallback(default_values))
     }
+
+    /// Serializes this configuration in the given file format, e.g. to save
+    /// the effective configuration after loading it from multiple sources.
+    /// Fields are written in declaration order, i.e. the order in
+    /// [`Config::META`]. Unlike `toml::template_with_values`, no comments are
+    /// emitted.
+    ///
+    /// `Self` has to implement `Serialize` in a way that matches its
+    /// `Deserialize` impl, so that the output can be loaded again. Returns an
+    /// error if serialization fails. Note that `HashMap` fields are written
+    /// in their (random) iteration order, so use `BTreeMap` if you need
+    /// deterministic output.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use confique::{Config, FileFormat};
+    ///
+    /// #[derive(Config, serde::Serialize)]
+    /// struct Conf {
+    ///     #[config(default = 8080)]
+    ///     port: u16,
+    /// }
+    ///
+    /// # #[cfg(feature = "toml")]
+    /// # {
+    /// let conf = Conf::builder().load()?;
+    /// assert_eq!(conf.dump(FileFormat::Toml)?, "port = 8080\n");
+    /// # }
+    /// # Ok::<_, confique::Error>(())
+    /// ```
+    #[cfg(any(feature = "toml", feature = "yaml", feature = "json5"))]
+    fn dump(&self, format: FileFormat) -> Result<String, Error>
+    where
+        Self: serde::Serialize,
+    {
+        format.serialize(self)
+    }
+
+    /// Logs the effective configuration via the `log` crate, e.g. on
+    /// startup: one `info` message per leaf field in the format
+    /// `path = value`, like `http.port = 8080`. Values of fields marked with
+    /// `#[config(secret)]` are replaced by `***`. Fields are logged in
+    /// declaration order, with values formatted like in
+    /// [`flatten_partial`]. If serialization fails, a warning is logged
+    /// instead.
+    ///
+    /// `Self` should implement `Serialize` with the field names unchanged
+    /// (i.e. no `#[serde(rename)]`), as nested and secret fields are looked
+    /// up in [`Config::META`] by name. Values of fields that cannot be found
+    /// there are replaced by `***` as well, so that renamed secrets are not
+    /// leaked.
+    ///
+    /// ```
+    /// use confique::Config;
+    ///
+    /// #[derive(Config, serde::Serialize)]
+    /// struct Conf {
+    ///     #[config(default = "admin")]
+    ///     username: String,
+    ///     #[config(default = "hunter2", secret)]
+    ///     password: String,
+    /// }
+    ///
+    /// let conf = Conf::builder().load()?;
+    ///
+    /// // Logs `username = admin` and `password = ***`.
+    /// conf.log_effective();
+    /// # Ok::<_, confique::Error>(())
+    /// ```
+    #[cfg(feature = "log")]
+    fn log_effective(&self)
+    where
+        Self: serde::Serialize,
+    {
+        match flatten::flatten_config(self) {
+            Ok(fields) => {
+                for (path, value) in fields {
+                    log::info!("{path} = {value}");
+                }
+            }
+            Err(e) => log::warn!("failed to log effective configuration: {e}"),
+        }
+    }
 }
 
 /// A potentially partial configuration object that can be directly deserialized
@@ -659,14 +1380,26 @@ pub trait Partial: for<'de> Deserialize<'de> {
     fn default_values() -> Self;
 
     /// Loads values from environment variables. This is only relevant for
-    /// fields annotated with `#[config(env = "...")]`: all fields not
-    /// annotated `env` will be `None`.
+    /// fields annotated with `#[config(env = "...")]` or
+    /// `#[config(env_auto)]`: all other fields will be `None`.
     ///
     /// If the env variable corresponding to a field is not set, that field is
     /// `None`. If it is set but it failed to deserialize into the target type,
     /// an error is returned.
     fn from_env() -> Result<Self, Error>;
 
+    /// Like [`from_env`][Partial::from_env], but uses `prefix` (instead of
+    /// the prefix specified via `#[config(env_prefix = "...")]`) for the keys
+    /// of fields annotated with `#[config(env_auto)]`. Nested configurations
+    /// receive `prefix` extended by their field name (e.g. `APP_HTTP_` for a
+    /// field `http` with the prefix `APP_`).
+    ///
+    /// The default implementation ignores `prefix` and calls `from_env`.
+    fn from_env_with_prefix(prefix: &str) -> Result<Self, Error> {
+        let _ = prefix;
+        Self::from_env()
+    }
+
     /// Combines two partial configuration objects. `self` has a higher
     /// priority; missing values in `self` are filled with values in `fallback`,
     /// if they exist. The semantics of this method is basically like in
@@ -680,4 +1413,34 @@ pub trait Partial: for<'de> Deserialize<'de> {
     /// configuration are set. If this returns `true`, `Config::from_partial`
     /// will not return an error.
     fn is_complete(&self) -> bool;
+
+    /// Semantically private: appends the paths of all fields included via
+    /// `#[config(extend)]` (relative to `prefix`) to `out`. String values
+    /// can't be loaded for those, see `values.rs`.
+    #[doc(hidden)]
+    fn __extended_paths(prefix: &str, out: &mut Vec<String>) {
+        let _ = (prefix, out);
+    }
+}
+
+/// A partial type for a field whose type does not implement [`Config`], used
+/// via `#[config(with_partial = ...)]` (see [the derive
+/// macro][macro@Config#with_partial]).
+///
+/// This is an expert feature to integrate types you don't control (e.g. from
+/// third-party crates) into the layered loading: the field is treated like a
+/// nested configuration, with `Self` being its partial type. `Self` is merged
+/// with other layers via [`Partial::with_fallback`] like any other partial
+/// type and only converted into the actual field type at the very end.
+pub trait FieldPartial: Partial {
+    /// The type of the field.
+    type Value;
+
+    /// Description of the fields of this partial type, used for templates
+    /// like the `META` of nested configurations.
+    const META: meta::Meta;
+
+    /// Converts the fully merged partial value into the field value. Is called
+    /// inside `Config::from_partial` of the struct containing the field.
+    fn into_value(self) -> Result<Self::Value, Error>;
 }
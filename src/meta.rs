@@ -1,5 +1,40 @@
 //! Types for [`Config::META`][super::Config::META]. Represent information about
 //! a configuration type.
+//!
+//! With the `json` feature, all types implement `serde::Serialize` and
+//! [`to_json`] converts the meta information to JSON, e.g. to write it to a
+//! file for external tooling like documentation pipelines. For example,
+//! `meta::to_json(&Conf::META)` results in something like this (shortened):
+//!
+//! ```text
+//! {
+//!   "name": "Conf",
+//!   "doc": [" The configuration."],
+//!   "fields": [
+//!     {
+//!       "name": "port",
+//!       "doc": [],
+//!       "hidden_in_template": false,
+//!       "kind": {
+//!         "type": "leaf",
+//!         "env": "PORT",
+//!         "has_parse_env": false,
+//!         "has_deserialize_with": false,
+//!         "example": null,
+//!         "secret": false,
+//!         "kind": { "type": "required", "default": 8080 }
+//!       }
+//!     },
+//!     {
+//!       "name": "log",
+//!       "doc": [],
+//!       "hidden_in_template": false,
+//!       "kind": { "type": "nested", "meta": { "name": "LogConf", ... } }
+//!     }
+//!   ],
+//!   "preambles": { "toml": null, "yaml": null, "json5": null }
+//! }
+//! ```
 
 use core::fmt;
 
@@ -9,6 +44,7 @@ use core::fmt;
 
 /// Root type.
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
 pub struct Meta {
     /// The type (struct) name.
     pub name: &'static str,
@@ -17,19 +53,256 @@ pub struct Meta {
     pub doc: &'static [&'static str],
 
     pub fields: &'static [Field],
+
+    /// Format-specific text emitted at the very top of templates, set via
+    /// `#[config(toml_preamble = "...")]` and similar attributes.
+    pub preambles: Preambles,
+}
+
+impl Meta {
+    /// Returns the field at the given path, which consists of field names
+    /// joined by `.`, e.g. `http.headers.username`. Returns `None` if there
+    /// is no such field, including when an intermediate segment refers to a
+    /// leaf field.
+    ///
+    /// ```
+    /// use confique::{Config, meta::FieldKind};
+    ///
+    /// #[derive(Config)]
+    /// struct Conf {
+    ///     #[config(nested)]
+    ///     http: HttpConf,
+    /// }
+    ///
+    /// #[derive(Config)]
+    /// struct HttpConf {
+    ///     /// The port to listen on.
+    ///     port: u16,
+    /// }
+    ///
+    /// # fn main() {
+    /// let field = Conf::META.find("http.port").unwrap();
+    /// assert_eq!(field.doc, [" The port to listen on."]);
+    /// assert!(Conf::META.find("http.host").is_none());
+    /// # }
+    /// ```
+    pub fn find(&self, path: &str) -> Option<&'static Field> {
+        let (first, rest) = match path.split_once('.') {
+            Some((first, rest)) => (first, Some(rest)),
+            None => (path, None),
+        };
+
+        let field = self.fields.iter().find(|f| f.name == first)?;
+        match (rest, &field.kind) {
+            (None, _) => Some(field),
+            (Some(rest), FieldKind::Nested { meta }) => meta.find(rest),
+            (Some(_), FieldKind::Leaf { .. }) => None,
+        }
+    }
+
+    /// Returns all leaf fields (recursively) in declaration order, together
+    /// with their path in the format accepted by [`find`][Self::find], e.g.
+    /// `http.port`. Useful for tools generating output per field from the
+    /// meta information, e.g. their default or example values.
+    pub fn leaf_fields(&self) -> Vec<(String, &'static Field)> {
+        fn collect(meta: &Meta, prefix: &str, out: &mut Vec<(String, &'static Field)>) {
+            for field in meta.fields {
+                let path = if prefix.is_empty() {
+                    field.name.to_owned()
+                } else {
+                    format!("{prefix}.{}", field.name)
+                };
+                match &field.kind {
+                    FieldKind::Leaf { .. } => out.push((path, field)),
+                    FieldKind::Nested { meta } => collect(meta, &path, out),
+                }
+            }
+        }
+
+        let mut out = Vec::new();
+        collect(self, "", &mut out);
+        out
+    }
+
+    /// Returns a fingerprint of the schema described by this meta: the names
+    /// of all fields (recursively), whether they are nested, optional or
+    /// required, and their default values. Useful to detect whether the
+    /// configuration schema changed, e.g. across deployments.
+    ///
+    /// Everything else, like doc comments, env keys, the order of fields and
+    /// the name of the struct, does not influence the fingerprint. The value
+    /// is deterministic and does not change between runs, platforms or
+    /// compiler versions. It might change with new confique versions, though
+    /// (which will be mentioned in the changelog).
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = Fnv::new();
+        hasher.meta(self);
+        hasher.0
+    }
+}
+
+/// Converts `meta` to JSON, including all nested metas. See the
+/// [module docs][self] for the format.
+#[cfg(feature = "json")]
+pub fn to_json(meta: &Meta) -> serde_json::Value {
+    serde_json::to_value(meta).expect("serializing meta information cannot fail")
+}
+
+/// 64-bit FNV-1a hasher for `Meta::fingerprint`. We don't use `std`'s
+/// `DefaultHasher` as its algorithm is not guaranteed to be stable.
+struct Fnv(u64);
+
+impl Fnv {
+    fn new() -> Self {
+        Self(0xcbf29ce484222325)
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 ^= u64::from(*b);
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+
+    /// Hashes a string prefixed with its length to avoid ambiguities.
+    fn str(&mut self, s: &str) {
+        self.bytes(&(s.len() as u64).to_le_bytes());
+        self.bytes(s.as_bytes());
+    }
+
+    fn meta(&mut self, meta: &Meta) {
+        let mut fields = meta.fields.iter().collect::<Vec<_>>();
+        fields.sort_by_key(|f| f.name);
+
+        self.bytes(&(fields.len() as u64).to_le_bytes());
+        for field in fields {
+            self.str(field.name);
+            match &field.kind {
+                FieldKind::Nested { meta } => {
+                    self.bytes(b"n");
+                    self.meta(meta);
+                }
+                FieldKind::Leaf { kind: LeafKind::Optional, .. } => self.bytes(b"o"),
+                FieldKind::Leaf { kind: LeafKind::Required { default: None }, .. } => {
+                    self.bytes(b"r");
+                }
+                FieldKind::Leaf { kind: LeafKind::Required { default: Some(expr) }, .. } => {
+                    self.bytes(b"d");
+                    self.expr(expr);
+                }
+            }
+        }
+    }
+
+    /// Hashes a tag for the variant and the value as 128 bit little endian
+    /// integer. That way, `usize` and `isize` values are hashed the same on
+    /// all platforms.
+    fn integer(&mut self, i: Integer) {
+        let (tag, bytes) = match i {
+            Integer::U8(v) => (0u8, u128::from(v).to_le_bytes()),
+            Integer::U16(v) => (1, u128::from(v).to_le_bytes()),
+            Integer::U32(v) => (2, u128::from(v).to_le_bytes()),
+            Integer::U64(v) => (3, u128::from(v).to_le_bytes()),
+            Integer::U128(v) => (4, v.to_le_bytes()),
+            Integer::Usize(v) => (5, (v as u128).to_le_bytes()),
+            Integer::I8(v) => (6, i128::from(v).to_le_bytes()),
+            Integer::I16(v) => (7, i128::from(v).to_le_bytes()),
+            Integer::I32(v) => (8, i128::from(v).to_le_bytes()),
+            Integer::I64(v) => (9, i128::from(v).to_le_bytes()),
+            Integer::I128(v) => (10, v.to_le_bytes()),
+            Integer::Isize(v) => (11, (v as i128).to_le_bytes()),
+        };
+        self.bytes(&[tag]);
+        self.bytes(&bytes);
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Str(s) => {
+                self.bytes(b"s");
+                self.str(s);
+            }
+            Expr::Float(Float::F32(f)) => {
+                self.bytes(b"f");
+                self.bytes(&f.to_bits().to_le_bytes());
+            }
+            Expr::Float(Float::F64(f)) => {
+                self.bytes(b"F");
+                self.bytes(&f.to_bits().to_le_bytes());
+            }
+            Expr::Integer(i) => {
+                self.bytes(b"i");
+                self.integer(*i);
+            }
+            Expr::Bool(b) => self.bytes(if *b { b"t" } else { b"b" }),
+            Expr::Array(items) => {
+                self.bytes(b"a");
+                self.bytes(&(items.len() as u64).to_le_bytes());
+                items.iter().for_each(|item| self.expr(item));
+            }
+            Expr::Map(entries) => {
+                self.bytes(b"m");
+                self.bytes(&(entries.len() as u64).to_le_bytes());
+                for entry in *entries {
+                    self.expr(&entry.key.into());
+                    self.expr(&entry.value);
+                }
+            }
+        }
+    }
+}
+
+/// Format-specific template preambles. Only the ones of the root type are used.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+pub struct Preambles {
+    pub toml: Option<&'static str>,
+    pub yaml: Option<&'static str>,
+    pub json5: Option<&'static str>,
+}
+
+impl Preambles {
+    /// No preambles for any format.
+    pub const NONE: Self = Self { toml: None, yaml: None, json5: None };
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
 pub struct Field {
     pub name: &'static str,
     pub doc: &'static [&'static str],
+
+    /// Whether this field is excluded from generated templates, i.e. whether
+    /// it has the `#[config(no_template)]` attribute.
+    pub hidden_in_template: bool,
+
     pub kind: FieldKind,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+#[cfg_attr(feature = "json", serde(tag = "type", rename_all = "snake_case"))]
 pub enum FieldKind {
     Leaf {
         env: Option<&'static str>,
+
+        /// Whether the field has a custom `parse_env` function, i.e. its env
+        /// value is not deserialized like other env values.
+        has_parse_env: bool,
+
+        /// Whether the field has a custom `deserialize_with` function, i.e. it
+        /// might accept a different format than its type suggests.
+        has_deserialize_with: bool,
+
+        /// Example value set via `#[config(example = ...)]`. Unlike the
+        /// default value, it is not used when loading the configuration; it
+        /// only serves as documentation, e.g. for generated files.
+        example: Option<Expr>,
+
+        /// Whether the field is marked with `#[config(secret)]`, i.e. its
+        /// value should not be shown in logs and similar output.
+        secret: bool,
+
         kind: LeafKind,
     },
     Nested {
@@ -38,6 +311,8 @@ pub enum FieldKind {
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "json", derive(serde::Serialize))]
+#[cfg_attr(feature = "json", serde(tag = "type", rename_all = "snake_case"))]
 pub enum LeafKind {
     /// A leaf field with a non `Option<_>` type.
     Required { default: Option<Expr> },
@@ -140,16 +415,6 @@ impl From<MapKey> for Expr {
     }
 }
 
-impl Float {
-    #[cfg(feature = "toml")]
-    pub(crate) fn is_nan(&self) -> bool {
-        match self {
-            Float::F32(f) => f.is_nan(),
-            Float::F64(f) => f.is_nan(),
-        }
-    }
-}
-
 fn serialize_map<S>(map: &&'static [MapEntry], serializer: S) -> Result<S::Ok, S::Error>
 where
     S: serde::Serializer,
@@ -162,3 +427,112 @@ where
     }
     s.end()
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{test_utils::example1, Config};
+    use super::FieldKind;
+
+    #[test]
+    fn find() {
+        let meta = &example1::Conf::META;
+        let names = |path| meta.find(path).map(|f| f.name);
+
+        assert_eq!(names("site_name"), Some("site_name"));
+        assert_eq!(names("http"), Some("http"));
+        assert_eq!(names("http.headers.username"), Some("username"));
+        assert!(matches!(meta.find("log").unwrap().kind, FieldKind::Nested { .. }));
+
+        assert_eq!(names(""), None);
+        assert_eq!(names("nope"), None);
+        assert_eq!(names("http."), None);
+        assert_eq!(names("http.nope"), None);
+        assert_eq!(names("site_name.nope"), None);
+        assert_eq!(names("http.headers.username.nope"), None);
+    }
+
+    #[test]
+    fn leaf_fields() {
+        let paths = example1::Conf::META.leaf_fields().into_iter()
+            .map(|(path, _)| path)
+            .collect::<Vec<_>>();
+        assert_eq!(paths, [
+            "site_name",
+            "http.port",
+            "http.headers.username",
+            "http.headers.display_name",
+            "http.headers.allowed",
+            "http.headers.score",
+            "http.bind",
+            "http.buffer_size",
+            "log.stdout",
+            "log.file",
+        ]);
+    }
+
+    #[test]
+    #[cfg(feature = "json")]
+    fn to_json() {
+        use crate::test_utils::include_format_output;
+
+        let expected: serde_json::Value = serde_json::from_str(
+            include_format_output!("1-meta.json"),
+        ).unwrap();
+        assert_eq!(super::to_json(&example1::Conf::META), expected);
+    }
+
+    #[test]
+    fn fingerprint() {
+        use crate::test_utils::example2;
+        use super::{Expr, Field, FieldKind, Integer, LeafKind, Meta, Preambles};
+
+        let fp1 = example1::Conf::META.fingerprint();
+        assert_eq!(fp1, example1::Conf::META.fingerprint());
+        assert_ne!(fp1, example2::Conf::META.fingerprint());
+
+        const fn leaf(name: &'static str, doc: &'static [&'static str], kind: LeafKind) -> Field {
+            Field {
+                name,
+                doc,
+                hidden_in_template: false,
+                kind: FieldKind::Leaf {
+                    env: None,
+                    has_parse_env: false,
+                    has_deserialize_with: false,
+                    example: None,
+                    secret: false,
+                    kind,
+                },
+            }
+        }
+        const fn meta(fields: &'static [Field]) -> Meta {
+            Meta { name: "Conf", doc: &[], fields, preambles: Preambles::NONE }
+        }
+        const PORT: LeafKind = LeafKind::Required {
+            default: Some(Expr::Integer(Integer::U16(8080))),
+        };
+
+        const BASE: Meta = meta(&[leaf("port", &[], PORT), leaf("host", &[], LeafKind::Optional)]);
+        const DOCS_AND_ORDER: Meta = meta(&[
+            leaf("host", &[" The host."], LeafKind::Optional),
+            leaf("port", &[" The port."], PORT),
+        ]);
+        const OTHER_DEFAULT: Meta = meta(&[
+            leaf("port", &[], LeafKind::Required { default: Some(Expr::Integer(Integer::U16(80))) }),
+            leaf("host", &[], LeafKind::Optional),
+        ]);
+        const OTHER_TYPE: Meta = meta(&[
+            leaf("port", &[], LeafKind::Required { default: Some(Expr::Integer(Integer::U32(8080))) }),
+            leaf("host", &[], LeafKind::Optional),
+        ]);
+        const REQUIRED: Meta = meta(&[
+            leaf("port", &[], PORT),
+            leaf("host", &[], LeafKind::Required { default: None }),
+        ]);
+
+        assert_eq!(BASE.fingerprint(), DOCS_AND_ORDER.fingerprint());
+        assert_ne!(BASE.fingerprint(), OTHER_DEFAULT.fingerprint());
+        assert_ne!(BASE.fingerprint(), OTHER_TYPE.fingerprint());
+        assert_ne!(BASE.fingerprint(), REQUIRED.fingerprint());
+    }
+}
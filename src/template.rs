@@ -6,7 +6,7 @@
 
 use std::fmt;
 
-use crate::meta::{Meta, FieldKind, LeafKind, Expr};
+use crate::meta::{Meta, FieldKind, LeafKind, Expr, Preambles};
 
 
 /// Trait abstracting over the format differences when it comes to formatting a
@@ -30,12 +30,22 @@ pub(crate) trait Formatter {
     /// Write a commented-out field with optional value, e.g. `format!("#{name} = {value}")`.
     fn disabled_field(&mut self, name: &'static str, value: Option<&'static Expr>);
 
+    /// Write a field with the given value, e.g. `format!("{name} = {value}")`.
+    /// `value` is already formatted in the target format.
+    fn enabled_field(&mut self, name: &'static str, value: &str);
+
     /// Start a nested configuration section with the given name.
     fn start_nested(&mut self, name: &'static str, doc: &[&'static str]);
 
     /// End a nested configuration section.
     fn end_nested(&mut self);
 
+    /// Returns the preamble for this format, which is emitted verbatim at the
+    /// very top of the template. Default impl returns `None`.
+    fn preamble(&self, _preambles: &Preambles) -> Option<&'static str> {
+        None
+    }
+
     /// Called after the global docs are written and before and fields are
     /// emitted. Default impl does nothing.
     fn start_main(&mut self) {}
@@ -119,9 +129,25 @@ pub struct FormatOptions {
     /// Default: 1.
     pub nested_field_gap: u8,
 
+    /// Whether to also include the doc comment of nested configuration types
+    /// (on the struct definition) for nested sections, after the doc comment
+    /// of the field. Useful for reusable nested types that document
+    /// themselves. Default: `false`.
+    pub nested_type_docs: bool,
+
+    /// If set, a key with the given name and version number is emitted as
+    /// first value of the template (not commented out), e.g.
+    /// `config_version = 1`. This marks files created from the template with
+    /// the version of your configuration format, which helps with evolving
+    /// it. Note that confique does not check this key when loading; to load
+    /// such files with `deny_unknown_fields`, add a field for it to your
+    /// configuration.
+    ///
+    /// Default: `None`.
+    pub version_key: Option<(&'static str, u64)>,
+
     // Potential future options:
     // - Comment out default values (`#foo = 3` vs `foo = 3`)
-    // - Which docs to include from nested objects
 }
 
 impl FormatOptions {
@@ -137,6 +163,8 @@ impl Default for FormatOptions {
             env_keys: true,
             leaf_field_gap: None,
             nested_field_gap: 1,
+            nested_type_docs: false,
+            version_key: None,
         }
     }
 }
@@ -147,6 +175,47 @@ impl Default for FormatOptions {
 /// functions in the format-specific modules (e.g. `toml::format`,
 /// `yaml::format`).
 pub(crate) fn format(meta: &Meta, out: &mut impl Formatter, options: FormatOptions) {
+    format_with_values(meta, out, options, None);
+}
+
+/// Returns the formatted value for the leaf field with the given path.
+pub(crate) type ValueLookup<'a> = &'a dyn Fn(&[&'static str]) -> Option<String>;
+
+/// Returns a lookup function for `format_with_values` that returns the
+/// default values of `meta`, formatted with `P`. Used to emit a template with
+/// all defaults enabled instead of commented-out.
+#[cfg(feature = "test-util")]
+pub(crate) fn default_lookup<P>(meta: &Meta) -> impl Fn(&[&'static str]) -> Option<String> + '_
+where
+    P: fmt::Display + From<&'static Expr>,
+{
+    move |path| match meta.find(&path.join("."))?.kind {
+        FieldKind::Leaf { kind: LeafKind::Required { default: Some(ref expr) }, .. } => {
+            Some(P::from(expr).to_string())
+        }
+        _ => None,
+    }
+}
+
+/// Like `format`, but emits actual values instead of commented-out default
+/// values for all fields for which `values` returns `Some`. `values` is called
+/// with the path to a leaf field and has to return the value already
+/// formatted for the target format.
+pub(crate) fn format_with_values(
+    meta: &Meta,
+    out: &mut impl Formatter,
+    options: FormatOptions,
+    values: Option<ValueLookup<'_>>,
+) {
+    if let Some(preamble) = out.preamble(&meta.preambles) {
+        let buffer = out.buffer();
+        buffer.push_str(preamble);
+        if !preamble.ends_with('\n') {
+            buffer.push('\n');
+        }
+        out.make_gap(1);
+    }
+
     // Print root docs.
     if options.comments {
         meta.doc.iter().for_each(|doc| out.comment(doc));
@@ -154,16 +223,29 @@ pub(crate) fn format(meta: &Meta, out: &mut impl Formatter, options: FormatOptio
 
     // Recursively format all nested objects and fields
     out.start_main();
-    format_impl(out, meta, &options);
+    if let Some((key, version)) = options.version_key {
+        if options.comments {
+            out.comment(" Version of the configuration format. Do not change!");
+        }
+        out.enabled_field(key, &version.to_string());
+        out.make_gap(options.nested_field_gap.max(options.leaf_field_gap()));
+    }
+    format_impl(out, meta, &options, values, &mut Vec::new());
     out.end_main();
     out.assert_single_trailing_newline();
 }
 
 
-fn format_impl(out: &mut impl Formatter, meta: &Meta, options: &FormatOptions) {
+fn format_impl(
+    out: &mut impl Formatter,
+    meta: &Meta,
+    options: &FormatOptions,
+    values: Option<ValueLookup<'_>>,
+    path: &mut Vec<&'static str>,
+) {
     // Output all leaf fields first
     let leaf_fields = meta.fields.iter().filter_map(|f| match &f.kind {
-        FieldKind::Leaf { kind, env } => Some((f, kind, env)),
+        FieldKind::Leaf { kind, env, .. } if !f.hidden_in_template => Some((f, kind, env)),
         _ => None,
     });
     let mut emitted_anything = false;
@@ -193,8 +275,15 @@ fn format_impl(out: &mut impl Formatter, meta: &Meta, options: &FormatOptions) {
             }
         }
 
+        path.push(field.name);
+        let value = values.and_then(|values| values(path));
+        path.pop();
+
         match kind {
-            LeafKind::Optional => out.disabled_field(field.name, None),
+            LeafKind::Optional => match value {
+                Some(value) => out.enabled_field(field.name, &value),
+                None => out.disabled_field(field.name, None),
+            },
             LeafKind::Required { default } => {
                 // Emit comment about default value or the value being required.
                 if options.comments {
@@ -203,14 +292,17 @@ fn format_impl(out: &mut impl Formatter, meta: &Meta, options: &FormatOptions) {
                 }
 
                 // Emit the actual line with the name and optional value
-                out.disabled_field(field.name, default.as_ref());
+                match value {
+                    Some(value) => out.enabled_field(field.name, &value),
+                    None => out.disabled_field(field.name, default.as_ref()),
+                }
             }
         }
     }
 
     // Then all nested fields recursively
     let nested_fields = meta.fields.iter().filter_map(|f| match &f.kind {
-        FieldKind::Nested { meta } => Some((f, meta)),
+        FieldKind::Nested { meta } if !f.hidden_in_template => Some((f, meta)),
         _ => None,
     });
     for (field, meta) in nested_fields {
@@ -219,9 +311,20 @@ fn format_impl(out: &mut impl Formatter, meta: &Meta, options: &FormatOptions) {
         }
         emitted_anything = true;
 
-        let comments = if options.comments { field.doc } else { &[] };
-        out.start_nested(field.name, comments);
-        format_impl(out, meta, options);
+        let mut comments = Vec::new();
+        if options.comments {
+            comments.extend_from_slice(field.doc);
+            if options.nested_type_docs && !meta.doc.is_empty() {
+                if !comments.is_empty() {
+                    comments.push("");
+                }
+                comments.extend_from_slice(meta.doc);
+            }
+        }
+        out.start_nested(field.name, &comments);
+        path.push(field.name);
+        format_impl(out, meta, options, values, path);
+        path.pop();
         out.end_nested();
     }
 }
@@ -0,0 +1,129 @@
+//! Helpers to check your configuration types in your own test suite.
+
+use crate::{Config, Partial};
+
+
+/// Checks that all default values of `C` are valid and panics otherwise.
+/// Intended to be called in a test in your crate.
+///
+/// Default values (`#[config(default = ...)]`) are only deserialized and
+/// validated at runtime, when [`Partial::default_values`] is called, which
+/// panics if a default value is invalid (e.g. fails its field's `validate`
+/// function). Calling this function in a test catches such mistakes in your
+/// test suite instead of in production.
+///
+/// This function:
+/// - calls `default_values()`, which panics if any default value fails to
+///   deserialize or validate, and
+/// - if the defaults are complete (i.e. all required values have a default),
+///   also runs them through [`Config::from_partial`], which additionally runs
+///   struct validators and `required_if` checks, and panics if that fails.
+///
+/// # Example
+///
+/// ```
+/// use confique::Config;
+///
+/// #[derive(Config)]
+/// struct Conf {
+///     #[config(default = 8080, validate(*port >= 1024, "port must be >= 1024"))]
+///     port: u16,
+/// }
+///
+/// // Inside a `#[test]` function in your crate:
+/// confique::test::assert_defaults_valid::<Conf>();
+/// ```
+#[track_caller]
+pub fn assert_defaults_valid<C: Config>() {
+    let defaults = C::Partial::default_values();
+    if defaults.is_complete() {
+        if let Err(e) = C::from_partial(defaults) {
+            panic!("default values of `{}` are invalid: {e:#}", C::META.name);
+        }
+    }
+}
+
+/// Generates a template for `C` in every enabled file format (see the crate
+/// features), with all default values emitted as actual values, and checks
+/// that it can be loaded again. Panics otherwise. Intended to be called in a
+/// test in your crate.
+///
+/// This catches problems with how confique formats your default values in
+/// templates, e.g. values that are not valid in a format or that cannot be
+/// deserialized into the field type after being written to a file. If the
+/// defaults are complete (i.e. all required values have a default), the
+/// loaded values are also run through [`Config::from_partial`], like in
+/// [`assert_defaults_valid`]. If no file format is enabled, this does
+/// nothing.
+///
+/// # Example
+///
+/// ```
+/// use confique::Config;
+///
+/// #[derive(Config)]
+/// struct Conf {
+///     #[config(default = 0.5)]
+///     ratio: f32,
+///
+///     #[config(default = { "alice": 3, "bob": 5 })]
+///     weights: std::collections::HashMap<String, u32>,
+/// }
+///
+/// // Inside a `#[test]` function in your crate:
+/// confique::test::roundtrip_all_formats::<Conf>();
+/// ```
+#[track_caller]
+pub fn roundtrip_all_formats<C: Config>() {
+    #[cfg(feature = "toml")]
+    roundtrip::<C, _>("TOML", crate::toml::template_with_defaults::<C>(), |s| {
+        toml::from_str(s).map_err(|e| e.to_string())
+    });
+
+    #[cfg(feature = "yaml")]
+    roundtrip::<C, _>("YAML", crate::yaml::template_with_defaults::<C>(), |s| {
+        serde_yaml::from_str(s).map_err(|e| e.to_string())
+    });
+
+    #[cfg(feature = "json5")]
+    roundtrip::<C, _>("JSON5", crate::json5::template_with_defaults::<C>(), |s| {
+        json5::from_str(s).map_err(|e| e.to_string())
+    });
+}
+
+#[cfg(any(feature = "toml", feature = "yaml", feature = "json5"))]
+#[track_caller]
+fn roundtrip<C, F>(format: &str, template: String, parse: F)
+where
+    C: Config,
+    F: FnOnce(&str) -> Result<C::Partial, String>,
+{
+    let partial = match parse(&template) {
+        Ok(p) => p,
+        Err(e) => panic!(
+            "{format} template of `{}` cannot be loaded: {e}\n\nTemplate:\n{template}",
+            C::META.name,
+        ),
+    };
+    if partial.is_complete() {
+        if let Err(e) = C::from_partial(partial) {
+            panic!(
+                "values loaded from {format} template of `{}` are invalid: {e:#}\n\n\
+                    Template:\n{template}",
+                C::META.name,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::{example1, example2, example3};
+
+    #[test]
+    fn roundtrip_examples() {
+        super::roundtrip_all_formats::<example1::Conf>();
+        super::roundtrip_all_formats::<example2::Conf>();
+        super::roundtrip_all_formats::<example3::Conf>();
+    }
+}
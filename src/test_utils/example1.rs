@@ -3,7 +3,7 @@ use std::{collections::HashMap, net::IpAddr, path::PathBuf};
 use crate as confique;
 use crate::Config;
 
-#[derive(Debug, Config)]
+#[derive(Debug, Config, serde::Serialize)]
 /// A sample configuration for our app.
 #[allow(dead_code)]
 pub struct Conf {
@@ -20,7 +20,7 @@ pub struct Conf {
 }
 
 /// Configuring the HTTP server of our app.
-#[derive(Debug, Config)]
+#[derive(Debug, Config, serde::Serialize)]
 #[allow(dead_code)]
 pub struct Http {
     /// The port the server will listen on.
@@ -34,9 +34,13 @@ pub struct Http {
     /// allow other users of the network to access the server.
     #[config(default = "127.0.0.1")]
     pub bind: IpAddr,
+
+    /// Internal tuning knob that should not show up in templates.
+    #[config(default = 4096, no_template)]
+    pub buffer_size: usize,
 }
 
-#[derive(Debug, Config)]
+#[derive(Debug, Config, serde::Serialize)]
 #[allow(dead_code)]
 pub struct Headers {
     /// The header in which the reverse proxy specifies the username.
@@ -57,7 +61,7 @@ pub struct Headers {
 }
 
 
-#[derive(Debug, Config)]
+#[derive(Debug, Config, serde::Serialize)]
 #[allow(dead_code)]
 pub struct LogConfig {
     /// If set to `true`, the app will log to stdout.
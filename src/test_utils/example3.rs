@@ -0,0 +1,24 @@
+use crate as confique;
+use crate::Config;
+
+/// Field names that are not valid bare keys in some formats.
+#[derive(Debug, Config)]
+#[allow(dead_code)]
+pub struct Conf {
+    /// A raw identifier.
+    #[config(default = "fox")]
+    pub r#type: String,
+
+    /// A non-ASCII identifier.
+    pub größe: Option<u32>,
+
+    #[config(nested)]
+    pub straße: Street,
+}
+
+#[derive(Debug, Config)]
+#[allow(dead_code)]
+pub struct Street {
+    #[config(default = 3)]
+    pub länge: u32,
+}
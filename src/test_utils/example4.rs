@@ -0,0 +1,14 @@
+use crate as confique;
+use crate::Config;
+
+/// Config with format-specific preambles.
+#[derive(Debug, Config)]
+#[config(toml_preamble = "#:schema ./schema.json")]
+#[config(yaml_preamble = "# yaml-language-server: $schema=./schema.json\n")]
+#[config(json5_preamble = "// json5 preamble")]
+#[allow(dead_code)]
+pub struct Conf {
+    /// The port.
+    #[config(default = 8080)]
+    pub port: u16,
+}
@@ -4,9 +4,10 @@
 use std::fmt::{self, Write};
 
 use crate::{
-    meta::{Expr, MapKey},
+    error::ErrorInner,
+    meta::{Expr, FieldKind, Float, LeafKind, MapKey, Meta, Preambles},
     template::{self, Formatter},
-    Config,
+    Config, Error,
 };
 
 
@@ -97,6 +98,148 @@ pub fn template<C: Config>(options: FormatOptions) -> String {
     out.finish()
 }
 
+/// Like [`template`], but emits the actual values of `config` instead of
+/// commented-out default values, while still including all doc comments. The
+/// result is a valid TOML file that can be loaded again, e.g. to export the
+/// current settings of your application.
+///
+/// The values are obtained by serializing `config`, so `C` has to implement
+/// `Serialize` in a way that matches its `Deserialize` impl, i.e. without
+/// renaming fields. Fields serialized as absent (e.g. `None` values of
+/// optional fields) are emitted commented-out, as in [`template`]. Fields
+/// annotated with `#[config(no_template)]` are not included.
+///
+/// Returns an error if `config` cannot be serialized to TOML.
+///
+/// # Example
+///
+/// ```
+/// # use pretty_assertions::assert_eq;
+/// use confique::{Config, toml::FormatOptions};
+///
+/// #[derive(Config, serde::Serialize)]
+/// struct Conf {
+///     /// Port to listen on.
+///     #[config(default = 8080)]
+///     port: u16,
+///
+///     /// Name of the instance.
+///     name: Option<String>,
+/// }
+///
+/// const EXPECTED: &str = "\
+/// ## Port to listen on.
+/// ##
+/// ## Default value: 8080
+/// port = 9000
+///
+/// ## Name of the instance.
+/// ##name =
+/// ";
+///
+/// fn main() {
+///     let conf = Conf { port: 9000, name: None };
+///     let toml = confique::toml::template_with_values(&conf, FormatOptions::default());
+///     assert_eq!(toml.unwrap(), EXPECTED);
+/// }
+/// ```
+pub fn template_with_values<C>(config: &C, options: FormatOptions) -> Result<String, Error>
+where
+    C: Config + serde::Serialize,
+{
+    let root = toml::Value::try_from(config)
+        .map_err(|e| ErrorInner::Serialization { err: Box::new(e) })?;
+    let lookup = |path: &[&'static str]| {
+        path.iter()
+            .try_fold(&root, |value, key| value.get(key))
+            .map(|value| value.to_string())
+    };
+
+    let mut out = TomlFormatter::new(&options);
+    template::format_with_values(&C::META, &mut out, options.general, Some(&lookup));
+    Ok(out.finish())
+}
+
+/// Serializes `config` into a minimal TOML file: only values that differ from
+/// their default value (as specified via `#[config(default = ...)]`) are
+/// included. Optional fields that are `None` and nested tables without any
+/// differing values are omitted as well. Loading the result (with defaults
+/// applied) yields the same configuration again.
+///
+/// Like with [`template_with_values`], `C` has to implement `Serialize` in a
+/// way that matches its `Deserialize` impl. Returns an error if `config`
+/// cannot be serialized to TOML.
+///
+/// # Example
+///
+/// ```
+/// use confique::Config;
+///
+/// #[derive(Config, serde::Serialize)]
+/// struct Conf {
+///     #[config(default = 8080)]
+///     port: u16,
+///
+///     #[config(default = "localhost")]
+///     host: String,
+/// }
+///
+/// fn main() {
+///     let conf = Conf { port: 9000, host: "localhost".into() };
+///     let toml = confique::toml::dump_minimal(&conf).unwrap();
+///     assert_eq!(toml, "port = 9000\n");
+/// }
+/// ```
+pub fn dump_minimal<C>(config: &C) -> Result<String, Error>
+where
+    C: Config + serde::Serialize,
+{
+    let mut root = toml::Table::try_from(config)
+        .map_err(|e| ErrorInner::Serialization { err: Box::new(e) })?;
+    remove_defaults(&C::META, &mut root);
+    let out = toml::to_string(&root)
+        .map_err(|e| ErrorInner::Serialization { err: Box::new(e) })?;
+    Ok(out)
+}
+
+/// Removes all values from `table` that are equal to their default value
+/// according to `meta`, as well as all nested tables that end up empty.
+fn remove_defaults(meta: &Meta, table: &mut toml::Table) {
+    for field in meta.fields {
+        match &field.kind {
+            FieldKind::Nested { meta } => {
+                if let Some(toml::Value::Table(nested)) = table.get_mut(field.name) {
+                    remove_defaults(meta, nested);
+                    if nested.is_empty() {
+                        table.remove(field.name);
+                    }
+                }
+            }
+            FieldKind::Leaf { kind: LeafKind::Required { default: Some(default) }, .. } => {
+                let is_default = table.get(field.name)
+                    .zip(toml::Value::try_from(default).ok())
+                    .map_or(false, |(value, default)| *value == default);
+                if is_default {
+                    table.remove(field.name);
+                }
+            }
+            FieldKind::Leaf { .. } => {}
+        }
+    }
+}
+
+/// Like [`template`] with default options, but with all default values
+/// emitted as actual values instead of commented-out. Used by
+/// [`crate::test::roundtrip_all_formats`].
+#[cfg(feature = "test-util")]
+pub(crate) fn template_with_defaults<C: Config>() -> String {
+    let options = FormatOptions::default();
+    let lookup = template::default_lookup::<PrintExpr>(&C::META);
+    let mut out = TomlFormatter::new(&options);
+    template::format_with_values(&C::META, &mut out, options.general, Some(&lookup));
+    out.finish()
+}
+
 struct TomlFormatter {
     indent: u8,
     buffer: String,
@@ -121,6 +264,10 @@ impl TomlFormatter {
 impl Formatter for TomlFormatter {
     type ExprPrinter = PrintExpr<'static>;
 
+    fn preamble(&self, preambles: &Preambles) -> Option<&'static str> {
+        preambles.toml
+    }
+
     fn buffer(&mut self) -> &mut String {
         &mut self.buffer
     }
@@ -130,18 +277,28 @@ impl Formatter for TomlFormatter {
         writeln!(self.buffer, "#{comment}").unwrap();
     }
 
-    fn disabled_field(&mut self, name: &str, value: Option<&'static Expr>) {
+    fn disabled_field(&mut self, name: &'static str, value: Option<&'static Expr>) {
+        let name = PrintKey(name);
         match value.map(PrintExpr) {
             None => self.comment(format_args!("{name} =")),
             Some(v) => self.comment(format_args!("{name} = {v}")),
         };
     }
 
+    fn enabled_field(&mut self, name: &'static str, value: &str) {
+        self.emit_indentation();
+        writeln!(self.buffer, "{} = {value}", PrintKey(name)).unwrap();
+    }
+
     fn start_nested(&mut self, name: &'static str, doc: &[&'static str]) {
         self.stack.push(name);
         doc.iter().for_each(|doc| self.comment(doc));
         self.emit_indentation();
-        writeln!(self.buffer, "[{}]", self.stack.join(".")).unwrap();
+        let path = self.stack.iter()
+            .map(|key| PrintKey(key).to_string())
+            .collect::<Vec<_>>()
+            .join(".");
+        writeln!(self.buffer, "[{path}]").unwrap();
     }
 
     fn end_nested(&mut self) {
@@ -179,7 +336,7 @@ impl fmt::Display for PrintExpr<'_> {
                     }
 
                     match entry.key {
-                        MapKey::Str(s) if is_valid_bare_key(s) => f.write_str(s)?,
+                        MapKey::Str(s) => PrintKey(s).fmt(f)?,
                         _ => PrintExpr(&entry.key.into()).fmt(f)?,
                     }
                     f.write_str(" = ")?;
@@ -193,9 +350,12 @@ impl fmt::Display for PrintExpr<'_> {
             // well with floats, not rounding them appropriately. See:
             // https://github.com/toml-rs/toml/issues/494
             //
-            // For all non-NAN floats, the `Display` output is compatible with
-            // TOML.
-            Expr::Float(fv) if !fv.is_nan() => fv.fmt(f),
+            // For all non-NAN floats, the `Debug` output is compatible with
+            // TOML. `Display` is not, as it never uses scientific notation and
+            // omits `.0`, e.g. `1e20` would be printed as an integer that is
+            // too large for TOML.
+            Expr::Float(Float::F32(v)) if !v.is_nan() => fmt::Debug::fmt(v, f),
+            Expr::Float(Float::F64(v)) if !v.is_nan() => fmt::Debug::fmt(v, f),
 
             // All these other types can simply be serialized as is.
             Expr::Str(_) | Expr::Float(_) | Expr::Integer(_) | Expr::Bool(_) | Expr::Array(_) => {
@@ -208,8 +368,21 @@ impl fmt::Display for PrintExpr<'_> {
     }
 }
 
+/// Helper to emit a key into TOML, quoting it if it's not a valid bare key.
+struct PrintKey(&'static str);
+
+impl fmt::Display for PrintKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if is_valid_bare_key(self.0) {
+            f.write_str(self.0)
+        } else {
+            PrintExpr(&Expr::Str(self.0)).fmt(f)
+        }
+    }
+}
+
 fn is_valid_bare_key(s: &str) -> bool {
-    s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
 }
 
 #[cfg(test)]
@@ -217,7 +390,7 @@ mod tests {
     use pretty_assertions::assert_str_eq;
 
     use crate::test_utils::{self, include_format_output};
-    use super::{template, FormatOptions};
+    use super::{dump_minimal, template, template_with_values, FormatOptions};
 
     #[test]
     fn default() {
@@ -254,4 +427,147 @@ mod tests {
         let out = template::<test_utils::example2::Conf>(Default::default());
         assert_str_eq!(&out, include_format_output!("2-default.toml"));
     }
+
+    #[test]
+    fn special_keys() {
+        let out = template::<test_utils::example3::Conf>(Default::default());
+        assert_str_eq!(&out, include_format_output!("3-default.toml"));
+    }
+
+    #[test]
+    fn nested_type_docs() {
+        let mut options = FormatOptions::default();
+        options.general.nested_type_docs = true;
+        let out = template::<test_utils::example2::Conf>(options);
+        assert!(out.starts_with(concat!(
+            "# A sample configuration for our app.\n",
+            "\n",
+            "# Configuring the HTTP server of our app.\n",
+            "[http]\n",
+            "[http.headers]\n",
+        )), "{out}");
+
+        // Off by default.
+        let out = template::<test_utils::example2::Conf>(FormatOptions::default());
+        assert_str_eq!(&out, include_format_output!("2-default.toml"));
+    }
+
+    #[test]
+    fn version_key() {
+        let mut options = FormatOptions::default();
+        options.general.version_key = Some(("config_version", 3));
+        let out = template::<test_utils::example2::Conf>(options);
+        assert!(out.starts_with(concat!(
+            "# A sample configuration for our app.\n",
+            "\n",
+            "# Version of the configuration format. Do not change!\n",
+            "config_version = 3\n",
+            "\n",
+            "[http]\n",
+        )), "{out}");
+
+        let mut options = FormatOptions::default();
+        options.general.version_key = Some(("config_version", 3));
+        options.general.comments = false;
+        let out = template::<test_utils::example1::Conf>(options);
+        assert!(out.starts_with("config_version = 3\n\n#site_name =\n"), "{out}");
+    }
+
+    #[test]
+    fn with_values() {
+        use crate::{Config, Partial};
+        use test_utils::example1::{Conf, Headers, Http, LogConfig};
+
+        let conf = Conf {
+            site_name: "My \"cool\" site".into(),
+            http: Http {
+                port: 8080,
+                headers: Headers {
+                    username: "x-user".into(),
+                    display_name: "x-name".into(),
+                    allowed: vec!["content-type".into()],
+                    score: [("cookie".to_string(), 2.5)].into_iter().collect(),
+                },
+                bind: [0, 0, 0, 0].into(),
+                buffer_size: 8192,
+            },
+            log: LogConfig {
+                stdout: false,
+                file: None,
+            },
+        };
+        let out = template_with_values(&conf, FormatOptions::default()).unwrap();
+        assert_str_eq!(&out, include_format_output!("1-with-values.toml"));
+
+        // The output can be loaded again.
+        let partial: <Conf as Config>::Partial = toml::from_str(&out).unwrap();
+        let loaded = Conf::from_partial(partial.with_fallback(Partial::default_values())).unwrap();
+        assert_eq!(loaded.site_name, conf.site_name);
+        assert_eq!(loaded.http.bind, conf.http.bind);
+        assert_eq!(loaded.http.headers.score, conf.http.headers.score);
+        assert_eq!(loaded.log.stdout, conf.log.stdout);
+    }
+
+    #[test]
+    fn minimal() {
+        use test_utils::example1::{Conf, Headers, Http, LogConfig};
+
+        let mut conf = Conf {
+            site_name: "Foo".into(),
+            http: Http {
+                port: 8080,
+                headers: Headers {
+                    username: "x-username".into(),
+                    display_name: "x-display-name".into(),
+                    allowed: vec!["content-type".into(), "content-encoding".into()],
+                    score: [("cookie".to_string(), 1.5), ("server".to_string(), 12.7)]
+                        .into_iter()
+                        .collect(),
+                },
+                bind: [127, 0, 0, 1].into(),
+                buffer_size: 4096,
+            },
+            log: LogConfig {
+                stdout: true,
+                file: None,
+            },
+        };
+        let out = dump_minimal(&conf).unwrap();
+        assert_str_eq!(&out, "site_name = \"Foo\"\n\n[http]\nport = 8080\n");
+
+        conf.http.headers.display_name = "x-name".into();
+        conf.http.headers.score.insert("cookie".into(), 2.0);
+        conf.log.file = Some("/tmp/log".into());
+        let out = dump_minimal(&conf).unwrap();
+        assert_str_eq!(&out, concat!(
+            "site_name = \"Foo\"\n",
+            "\n",
+            "[http]\n",
+            "port = 8080\n",
+            "\n",
+            "[http.headers]\n",
+            "display_name = \"x-name\"\n",
+            "\n",
+            "[http.headers.score]\n",
+            "cookie = 2.0\n",
+            "server = 12.699999809265137\n",
+            "\n",
+            "[log]\n",
+            "file = \"/tmp/log\"\n",
+        ));
+    }
+
+    #[test]
+    fn preamble() {
+        let out = template::<test_utils::example4::Conf>(FormatOptions::default());
+        assert!(out.starts_with(concat!(
+            "#:schema ./schema.json\n",
+            "\n",
+            "# Config with format-specific preambles.\n",
+        )), "{out}");
+
+        // Types without preambles are unaffected.
+        let out = template::<test_utils::example1::Conf>(FormatOptions::default());
+        assert_str_eq!(&out, include_format_output!("1-default.toml"));
+    }
 }
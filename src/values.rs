@@ -0,0 +1,224 @@
+//! Loading partial configurations from string values addressed by field paths
+//! (e.g. `http.port`).
+//!
+//! The values are arranged in a tree mirroring the structure of the
+//! configuration type (as described by its [`Meta`]) and then deserialized
+//! into the partial type. Leaf values are deserialized like environment
+//! variables, i.e. via `env::Deserializer`.
+
+use std::{collections::HashMap, fs, io, path::Path};
+
+use serde::de::{value::MapDeserializer, IntoDeserializer, Visitor};
+
+use crate::{
+    env::{self, DeError},
+    error::ErrorInner,
+    internal::join_path,
+    meta::{FieldKind, Meta},
+    Error, Partial,
+};
+
+
+/// A node in the tree of values.
+enum Node {
+    Leaf {
+        path: String,
+        value: String,
+    },
+    Map(Vec<(&'static str, Node)>),
+}
+
+/// Calls `lookup` for the path of each leaf field in `meta` and deserializes
+/// all returned values into `P`. `source` is used for error messages and
+/// completes the sentence "failed to deserialize configuration from ".
+pub(crate) fn from_lookup<P: Partial>(
+    meta: &Meta,
+    lookup: &mut dyn FnMut(&str) -> Option<String>,
+    source: &str,
+) -> Result<P, Error> {
+    // Fields of `extend` fields are deserialized via `serde(flatten)`, which
+    // buffers all values without knowing their types. Our string values could
+    // then only be deserialized as strings, so we reject them with a clear
+    // error instead.
+    fn collect(
+        meta: &Meta,
+        prefix: &str,
+        extended: bool,
+        extended_paths: &[String],
+        lookup: &mut dyn FnMut(&str) -> Option<String>,
+        source: &str,
+    ) -> Result<Vec<(&'static str, Node)>, Error> {
+        let mut out = Vec::new();
+        for field in meta.fields {
+            let path = join_path(prefix, field.name);
+            let extended = extended || extended_paths.contains(&path);
+            match &field.kind {
+                FieldKind::Leaf { .. } => {
+                    if let Some(value) = lookup(&path) {
+                        if extended {
+                            let source = source.into();
+                            return Err(ErrorInner::ExtendedFieldValue { path, source }.into());
+                        }
+                        out.push((field.name, Node::Leaf { path, value }));
+                    }
+                }
+                FieldKind::Nested { meta } => {
+                    let entries = collect(meta, &path, extended, extended_paths, lookup, source)?;
+                    if !entries.is_empty() {
+                        out.push((field.name, Node::Map(entries)));
+                    }
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    let mut extended_paths = Vec::new();
+    P::__extended_paths("", &mut extended_paths);
+    deserialize(collect(meta, "", false, &extended_paths, lookup, source)?, source)
+}
+
+/// Parses `overrides` of the form `path=value` and deserializes them into
+/// `P`. Later overrides of the same path take precedence.
+pub(crate) fn from_overrides<P: Partial>(meta: &Meta, overrides: &[String]) -> Result<P, Error> {
+    let mut values = HashMap::new();
+    for o in overrides {
+        let invalid = |msg| ErrorInner::InvalidOverride { value: o.clone(), msg };
+        let (path, value) = o.split_once('=').ok_or_else(|| invalid("expected `path=value`"))?;
+        match meta.find(path).map(|f| &f.kind) {
+            Some(FieldKind::Leaf { .. }) => {}
+            Some(FieldKind::Nested { .. }) => {
+                return Err(invalid("path refers to a nested configuration, not a value").into());
+            }
+            None => return Err(invalid("no configuration value with this path").into()),
+        }
+        values.insert(path, value);
+    }
+
+    from_lookup(meta, &mut |path| values.remove(path).map(Into::into), "overrides")
+}
+
+/// Loads values from files in `dir`, where the file for the field `a.b` is
+/// `dir/a/b` or `dir/a.b`. Files that don't exist are skipped; a single
+/// trailing newline is removed from the file contents.
+pub(crate) fn from_dir<P: Partial>(meta: &Meta, dir: &Path) -> Result<P, Error> {
+    let mut error = None;
+    let mut lookup = |path: &str| {
+        if error.is_some() {
+            return None;
+        }
+
+        let candidates = [dir.join(path.replace('.', "/")), dir.join(path)];
+        for file in candidates {
+            match fs::read_to_string(&file) {
+                Ok(mut value) => {
+                    if value.ends_with('\n') {
+                        value.pop();
+                        if value.ends_with('\r') {
+                            value.pop();
+                        }
+                    }
+                    return Some(value);
+                }
+                // Directories are probably parents of other files.
+                Err(e) if e.kind() == io::ErrorKind::NotFound || file.is_dir() => {}
+                Err(err) => {
+                    error = Some(ErrorInner::Io { path: Some(file), err });
+                    return None;
+                }
+            }
+        }
+        None
+    };
+
+    let source = format!("directory '{}'", dir.display());
+    let out = from_lookup(meta, &mut lookup, &source);
+    match error {
+        Some(e) => Err(e.into()),
+        None => out,
+    }
+}
+
+fn deserialize<P: Partial>(entries: Vec<(&'static str, Node)>, source: &str) -> Result<P, Error> {
+    P::deserialize(Node::Map(entries)).map_err(|e| {
+        ErrorInner::Deserialization {
+            source: Some(source.into()),
+            err: Box::new(e),
+        }.into()
+    })
+}
+
+impl<'de> IntoDeserializer<'de, DeError> for Node {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+/// Forwards the given methods to `env::Deserializer` for leaf nodes and to
+/// serde's `MapDeserializer` for inner nodes.
+macro_rules! forward_to_inner {
+    ($( $method:ident ( $($arg:ident : $ty:ty),* ) )*) => {
+        $(
+            fn $method<V>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                match self {
+                    Self::Leaf { path, value } => env::Deserializer::new(value)
+                        .$method($($arg,)* visitor)
+                        .map_err(|e| DeError(format!("invalid value for `{path}`: {e}"))),
+                    Self::Map(entries) => MapDeserializer::new(entries.into_iter())
+                        .$method($($arg,)* visitor),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> serde::Deserializer<'de> for Node {
+    type Error = DeError;
+
+    // Leaf fields of the partial types are `Option<T>`, but a value being
+    // present means `Some`.
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    forward_to_inner! {
+        deserialize_any()
+        deserialize_bool()
+        deserialize_i8()
+        deserialize_i16()
+        deserialize_i32()
+        deserialize_i64()
+        deserialize_i128()
+        deserialize_u8()
+        deserialize_u16()
+        deserialize_u32()
+        deserialize_u64()
+        deserialize_u128()
+        deserialize_f32()
+        deserialize_f64()
+        deserialize_char()
+        deserialize_str()
+        deserialize_string()
+        deserialize_bytes()
+        deserialize_byte_buf()
+        deserialize_unit()
+        deserialize_unit_struct(name: &'static str)
+        deserialize_newtype_struct(name: &'static str)
+        deserialize_seq()
+        deserialize_tuple(len: usize)
+        deserialize_tuple_struct(name: &'static str, len: usize)
+        deserialize_map()
+        deserialize_struct(name: &'static str, fields: &'static [&'static str])
+        deserialize_enum(name: &'static str, variants: &'static [&'static str])
+        deserialize_identifier()
+        deserialize_ignored_any()
+    }
+}
@@ -0,0 +1,34 @@
+use std::fmt;
+
+
+/// A non-fatal problem with a loaded configuration, reported by a
+/// `#[config(warn(...))]` attribute. Obtained via
+/// [`Builder::load_with_warnings`][crate::Builder::load_with_warnings].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warning {
+    pub(crate) path: String,
+    pub(crate) msg: String,
+}
+
+impl Warning {
+    /// Path of the (nested) configuration that emitted this warning, e.g.
+    /// `http.tls`. Empty for the root configuration.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The message specified in the `warn` attribute.
+    pub fn message(&self) -> &str {
+        &self.msg
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.path.is_empty() {
+            f.write_str(&self.msg)
+        } else {
+            write!(f, "{}: {}", self.path, self.msg)
+        }
+    }
+}
@@ -4,7 +4,7 @@
 use std::fmt::{self, Write};
 
 use crate::{
-    meta::Expr,
+    meta::{Expr, Preambles},
     template::{self, Formatter},
     Config,
 };
@@ -99,6 +99,18 @@ pub fn template<C: Config>(options: FormatOptions) -> String {
     out.finish()
 }
 
+/// Like [`template`] with default options, but with all default values
+/// emitted as actual values instead of commented-out. Used by
+/// [`crate::test::roundtrip_all_formats`].
+#[cfg(feature = "test-util")]
+pub(crate) fn template_with_defaults<C: Config>() -> String {
+    let options = FormatOptions::default();
+    let lookup = template::default_lookup::<PrintExpr>(&C::META);
+    let mut out = YamlFormatter::new(&options);
+    template::format_with_values(&C::META, &mut out, options.general, Some(&lookup));
+    out.finish()
+}
+
 struct YamlFormatter {
     indent: u8,
     buffer: String,
@@ -123,6 +135,10 @@ impl YamlFormatter {
 impl Formatter for YamlFormatter {
     type ExprPrinter = PrintExpr<'static>;
 
+    fn preamble(&self, preambles: &Preambles) -> Option<&'static str> {
+        preambles.yaml
+    }
+
     fn buffer(&mut self) -> &mut String {
         &mut self.buffer
     }
@@ -132,17 +148,23 @@ impl Formatter for YamlFormatter {
         writeln!(self.buffer, "#{comment}").unwrap();
     }
 
-    fn disabled_field(&mut self, name: &str, value: Option<&'static Expr>) {
+    fn disabled_field(&mut self, name: &'static str, value: Option<&'static Expr>) {
+        let name = PrintExpr(&Expr::Str(name));
         match value.map(PrintExpr) {
             None => self.comment(format_args!("{name}:")),
             Some(v) => self.comment(format_args!("{name}: {v}")),
         };
     }
 
+    fn enabled_field(&mut self, name: &'static str, value: &str) {
+        self.emit_indentation();
+        writeln!(self.buffer, "{}: {value}", PrintExpr(&Expr::Str(name))).unwrap();
+    }
+
     fn start_nested(&mut self, name: &'static str, doc: &[&'static str]) {
         doc.iter().for_each(|doc| self.comment(doc));
         self.emit_indentation();
-        writeln!(self.buffer, "{name}:").unwrap();
+        writeln!(self.buffer, "{}:", PrintExpr(&Expr::Str(name))).unwrap();
         self.depth += 1;
     }
 
@@ -245,9 +267,38 @@ mod tests {
         assert_str_eq!(&out, include_format_output!("1-no-comments.yaml"));
     }
 
+    #[test]
+    fn version_key() {
+        let mut options = FormatOptions::default();
+        options.general.version_key = Some(("config_version", 3));
+        options.general.comments = false;
+        let out = template::<test_utils::example1::Conf>(options);
+        assert!(out.starts_with("config_version: 3\n\n#site_name:\n"), "{out}");
+    }
+
     #[test]
     fn immediately_nested() {
         let out = template::<test_utils::example2::Conf>(Default::default());
         assert_str_eq!(&out, include_format_output!("2-default.yaml"));
     }
+
+    #[test]
+    fn special_keys() {
+        let out = template::<test_utils::example3::Conf>(Default::default());
+        assert_str_eq!(&out, include_format_output!("3-default.yaml"));
+    }
+
+    #[test]
+    fn preamble() {
+        let out = template::<test_utils::example4::Conf>(FormatOptions::default());
+        assert!(out.starts_with(concat!(
+            "# yaml-language-server: $schema=./schema.json\n",
+            "\n",
+            "# Config with format-specific preambles.\n",
+        )), "{out}");
+
+        // Types without preambles are unaffected.
+        let out = template::<test_utils::example1::Conf>(FormatOptions::default());
+        assert_str_eq!(&out, include_format_output!("1-default.yaml"));
+    }
 }
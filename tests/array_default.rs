@@ -19,8 +19,13 @@ fn vec_u32() {
             meta::Field {
                 name: "bar",
                 doc: &[" A nice doc comment."],
+                hidden_in_template: false,
                 kind: meta::FieldKind::Leaf {
                     env: None,
+                    has_parse_env: false,
+                    has_deserialize_with: false,
+                    example: None,
+                    secret: false,
                     kind: meta::LeafKind::Required {
                         default: Some(meta::Expr::Array(&[
                             meta::Expr::Integer(meta::Integer::U32(1)),
@@ -31,6 +36,7 @@ fn vec_u32() {
                 },
             },
         ],
+        preambles: meta::Preambles::NONE,
     });
 
     let def = Foo::builder().load().unwrap();
@@ -64,6 +70,10 @@ fn inferred_type() {
         match actual {
             meta::FieldKind::Leaf {
                 env: None,
+                has_parse_env: false,
+                has_deserialize_with: false,
+                example: None,
+                secret: false,
                 kind: meta::LeafKind::Required {
                     default: Some(meta::Expr::Array(items)),
                 },
@@ -93,3 +103,42 @@ fn inferred_type() {
     assert_eq!(def.parens, vec![1.0, 2.0]);
     assert_eq!(def.fallback, std::time::Duration::new(13, 27));
 }
+
+#[test]
+fn consistent_suffixes() {
+    #[derive(Config)]
+    struct Foo {
+        #[config(default = [1u64, 2u64])]
+        all: Vec<u64>,
+
+        // The suffix-free literal has the type of its sibling, like in Rust.
+        #[config(default = [1u16, 2])]
+        some: Vec<u16>,
+
+        // Not inferable from the field type, but from the suffix.
+        #[config(default = [13u64, 27])]
+        duration: std::time::Duration,
+    }
+
+    let expected = |ctor: fn(u16) -> meta::Integer| {
+        [1, 2].map(|i| meta::Expr::Integer(ctor(i)))
+    };
+    let items = |i: usize| match &Foo::META.fields[i].kind {
+        meta::FieldKind::Leaf {
+            kind: meta::LeafKind::Required { default: Some(meta::Expr::Array(items)) },
+            ..
+        } => *items,
+        other => panic!("expected array default, found: {other:?}"),
+    };
+    assert_eq!(items(0), expected(|i| meta::Integer::U64(i.into())));
+    assert_eq!(items(1), expected(meta::Integer::U16));
+    assert_eq!(items(2), [
+        meta::Expr::Integer(meta::Integer::U64(13)),
+        meta::Expr::Integer(meta::Integer::U64(27)),
+    ]);
+
+    let def = Foo::builder().load().unwrap();
+    assert_eq!(def.all, [1, 2]);
+    assert_eq!(def.some, [1, 2]);
+    assert_eq!(def.duration, std::time::Duration::new(13, 27));
+}
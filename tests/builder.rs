@@ -0,0 +1,338 @@
+use std::net::IpAddr;
+
+use pretty_assertions::assert_eq;
+
+use confique::{Config, Partial};
+
+
+#[derive(Config)]
+struct Conf {
+    name: String,
+
+    #[config(nested)]
+    http: Http,
+}
+
+#[derive(Config)]
+struct Http {
+    port: u16,
+    bind: Option<IpAddr>,
+
+    #[config(default = true)]
+    enabled: bool,
+
+    #[config(validate(!user.is_empty(), "user must not be empty"))]
+    user: Option<String>,
+}
+
+#[test]
+fn lookup_fn() {
+    let mut requested = Vec::new();
+    let conf = Conf::builder()
+        .lookup_fn(move |path| {
+            requested.push(path.to_owned());
+            match path {
+                "name" => Some("peter".into()),
+                "http.port" => Some("8080".into()),
+                "http.enabled" => Some("no".into()),
+                "http.user" => {
+                    let expected = ["name", "http.port", "http.bind", "http.enabled", "http.user"];
+                    assert_eq!(requested, expected);
+                    None
+                }
+                _ => None,
+            }
+        })
+        .load()
+        .unwrap();
+
+    assert_eq!(conf.name, "peter");
+    assert_eq!(conf.http.port, 8080);
+    assert_eq!(conf.http.bind, None);
+    assert!(!conf.http.enabled);
+    assert_eq!(conf.http.user, None);
+
+    let err = Conf::builder()
+        .lookup_fn(|path| (path == "http.port").then(|| "foo".into()))
+        .load()
+        .err()
+        .unwrap();
+    assert_eq!(
+        format!("{err:#}"),
+        "failed to deserialize configuration from lookup function: invalid value for \
+            `http.port`: invalid value 'foo' for type u16: invalid digit found in string",
+    );
+
+    let err = Conf::builder()
+        .lookup_fn(|path| (path == "http.user").then(String::new))
+        .load()
+        .err()
+        .unwrap();
+    assert_eq!(
+        format!("{err:#}"),
+        "failed to deserialize configuration from lookup function: \
+            validation failed: user must not be empty",
+    );
+}
+
+#[test]
+fn builder_is_send() {
+    fn assert_send<T: Send>(_: &T) {}
+
+    assert_send(&Conf::builder());
+    assert_send(&Conf::builder().lookup_fn(|_| None).source_fn(|| Ok(Partial::empty())));
+}
+
+#[test]
+fn overrides() {
+    let conf = Conf::builder()
+        .overrides(["name=peter", "http.port=80", "http.bind=127.0.0.1", "http.port=8080"])
+        .overrides(vec!["name=ignored".to_owned(), "http.enabled=false".to_owned()])
+        .load()
+        .unwrap();
+
+    assert_eq!(conf.name, "peter");
+    assert_eq!(conf.http.port, 8080);
+    assert_eq!(conf.http.bind, Some(IpAddr::from([127, 0, 0, 1])));
+    assert!(!conf.http.enabled);
+
+    let load_err = |o: &str| {
+        let err = Conf::builder().overrides([o]).load().err().unwrap();
+        format!("{err:#}")
+    };
+    assert_eq!(load_err("name"), "invalid configuration override 'name': expected `path=value`");
+    assert_eq!(
+        load_err("http.prot=1"),
+        "invalid configuration override 'http.prot=1': no configuration value with this path",
+    );
+    assert_eq!(
+        load_err("http=1"),
+        "invalid configuration override 'http=1': path refers to a nested \
+            configuration, not a value",
+    );
+    assert_eq!(
+        load_err("http.port=high"),
+        "failed to deserialize configuration from overrides: invalid value for \
+            `http.port`: invalid value 'high' for type u16: invalid digit found in string",
+    );
+}
+
+// Only used for errors, so the fields are never read.
+#[derive(Config)]
+#[allow(dead_code)]
+struct HelpfulConf {
+    name: String,
+
+    #[config(nested)]
+    http: HelpfulHttp,
+}
+
+#[derive(Config)]
+#[allow(dead_code)]
+struct HelpfulHttp {
+    /// The port to listen on. Ports below 1024
+    /// usually require root.
+    #[config(env = "HELPFUL_ERRORS_PORT")]
+    port: u16,
+}
+
+#[test]
+fn dir_as_keys() {
+    let dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("dir_as_keys");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(dir.join("http")).unwrap();
+    std::fs::write(dir.join("name"), "peter\n").unwrap();
+    std::fs::write(dir.join("http/port"), "8080").unwrap();
+    std::fs::write(dir.join("http.enabled"), "false\r\n").unwrap();
+    std::fs::write(dir.join("unrelated"), "foo").unwrap();
+
+    let conf = Conf::builder().dir_as_keys(&dir).load().unwrap();
+    assert_eq!(conf.name, "peter");
+    assert_eq!(conf.http.port, 8080);
+    assert_eq!(conf.http.bind, None);
+    assert!(!conf.http.enabled);
+
+    std::fs::write(dir.join("http/port"), "high").unwrap();
+    let err = Conf::builder().dir_as_keys(&dir).load().err().unwrap();
+    assert_eq!(format!("{err:#}"), format!(
+        "failed to deserialize configuration from directory '{}': invalid value for \
+            `http.port`: invalid value 'high' for type u16: invalid digit found in string",
+        dir.display(),
+    ));
+
+    // Missing directories are fine.
+    let conf = Conf::builder()
+        .dir_as_keys(dir.join("missing"))
+        .overrides(["name=x", "http.port=1"])
+        .load()
+        .unwrap();
+    assert_eq!(conf.name, "x");
+
+    // Symlink loops result in an IO error for that file instead of hanging.
+    #[cfg(unix)]
+    {
+        std::fs::write(dir.join("http/port"), "8080").unwrap();
+        std::fs::remove_file(dir.join("name")).unwrap();
+        std::os::unix::fs::symlink(dir.join("name"), dir.join("name")).unwrap();
+        let err = Conf::builder().dir_as_keys(&dir).load().err().unwrap();
+        assert_eq!(err.to_string(), format!(
+            "IO error occured while reading configuration file '{}'",
+            dir.join("name").display(),
+        ));
+    }
+}
+
+#[test]
+fn helpful_errors() {
+    let err = HelpfulConf::builder().helpful_errors().overrides(["name=x"]).load().err().unwrap();
+    assert_eq!(
+        err.to_string(),
+        "required configuration value is missing: 'http.port'; set it in a configuration \
+            file or via environment variable `HELPFUL_ERRORS_PORT`; The port to listen on. \
+            Ports below 1024 usually require root.",
+    );
+
+    let err = HelpfulConf::builder().helpful_errors().overrides(["http.port=80"]).load().err().unwrap();
+    assert_eq!(
+        err.to_string(),
+        "required configuration value is missing: 'name'; set it in a configuration file",
+    );
+
+    // Not enabled by default.
+    let err = HelpfulConf::builder().overrides(["name=x"]).load().err().unwrap();
+    assert_eq!(err.to_string(), "required configuration value is missing: 'http.port'");
+}
+
+#[test]
+fn preloaded_many() {
+    #[derive(Config)]
+    struct Conf {
+        #[config(default = 1)]
+        a: u32,
+        b: u32,
+        c: Option<u32>,
+    }
+
+    type Partial = <Conf as Config>::Partial;
+
+    let conf = Conf::builder()
+        .preloaded_many([
+            Partial { a: None, b: Some(2), c: None },
+            Partial { a: None, b: Some(3), c: Some(4) },
+        ])
+        .load()
+        .unwrap();
+
+    assert_eq!(conf.a, 1);
+    assert_eq!(conf.b, 2);
+    assert_eq!(conf.c, Some(4));
+}
+
+#[test]
+fn stop_when_complete() {
+    #[derive(Config)]
+    struct Conf {
+        port: u16,
+        #[config(default = "localhost")]
+        host: String,
+        name: Option<String>,
+    }
+
+    type Partial = <Conf as Config>::Partial;
+    let unreachable = || -> Result<Partial, confique::Error> {
+        panic!("source loaded after configuration was complete")
+    };
+
+    // Stops after the second source, so the third source is never called and
+    // the optional value of the fourth is not used.
+    let conf = Conf::builder()
+        .stop_when_complete()
+        .preloaded(Partial { port: Some(80), host: None, name: None })
+        .preloaded(Partial { port: None, host: Some("example.com".into()), name: None })
+        .source_fn(unreachable)
+        .preloaded(Partial { port: None, host: None, name: Some("peter".into()) })
+        .load()
+        .unwrap();
+    assert_eq!(conf.port, 80);
+    assert_eq!(conf.host, "example.com");
+    assert_eq!(conf.name, None);
+
+    // Defaults do not count towards completeness, so all sources are loaded.
+    let conf = Conf::builder()
+        .stop_when_complete()
+        .preloaded(Partial { port: Some(80), host: None, name: None })
+        .preloaded(Partial { port: None, host: None, name: Some("peter".into()) })
+        .load()
+        .unwrap();
+    assert_eq!(conf.host, "localhost");
+    assert_eq!(conf.name.as_deref(), Some("peter"));
+
+    // Without the flag, all sources are loaded.
+    let conf = Conf::builder()
+        .preloaded(Partial { port: Some(80), host: Some("example.com".into()), name: None })
+        .preloaded(Partial { port: None, host: None, name: Some("peter".into()) })
+        .load()
+        .unwrap();
+    assert_eq!(conf.name.as_deref(), Some("peter"));
+}
+
+#[test]
+fn source_fn() {
+    #[derive(Config)]
+    struct Conf {
+        #[config(default = 8080)]
+        port: u16,
+        name: String,
+    }
+
+    type Partial = <Conf as Config>::Partial;
+
+    // Earlier sources have priority, the function only fills the gaps.
+    let conf = Conf::builder()
+        .preloaded(Partial { port: None, name: Some("peter".into()) })
+        .source_fn(|| Ok(Partial { port: Some(80), name: Some("susi".into()) }))
+        .load()
+        .unwrap();
+    assert_eq!(conf.port, 80);
+    assert_eq!(conf.name, "peter");
+
+    // Errors are propagated.
+    let err = Conf::builder()
+        .source_fn(|| Err(confique::Error::custom_source("vault unreachable")))
+        .load()
+        .err()
+        .unwrap();
+    assert_eq!(
+        err.display_chain().to_string(),
+        "failed to load configuration from custom source: vault unreachable",
+    );
+}
+
+#[test]
+fn source_with_timeout() {
+    use std::{thread, time::Duration};
+
+    #[derive(Config)]
+    struct Conf {
+        port: u16,
+    }
+
+    type Partial = <Conf as Config>::Partial;
+
+    let conf = Conf::builder()
+        .source_with_timeout(|| Ok(Partial { port: Some(80) }), Duration::from_secs(10))
+        .load()
+        .unwrap();
+    assert_eq!(conf.port, 80);
+
+    let slow = || {
+        thread::sleep(Duration::from_secs(10));
+        Ok(Partial { port: Some(80) })
+    };
+    let err = Conf::builder()
+        .source_with_timeout(slow, Duration::from_millis(50))
+        .load()
+        .err()
+        .unwrap();
+    assert_eq!(err.to_string(), "configuration source did not finish loading within 50ms");
+}
@@ -0,0 +1,16 @@
+//! Compile tests for the `Config` derive: invalid uses (e.g. attribute
+//! combinations that are not allowed) are checked against the expected error
+//! messages, allowed attribute combinations have to compile. Run with
+//! `TRYBUILD=overwrite` to update the `.stderr` files.
+
+#[test]
+fn compile_fail() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/compile_fail/*.rs");
+}
+
+#[test]
+fn compile_pass() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/compile_pass/*.rs");
+}
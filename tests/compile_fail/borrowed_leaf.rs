@@ -0,0 +1,8 @@
+use confique::Config;
+
+#[derive(Config)]
+struct Conf<'a> {
+    name: &'a str,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use std::borrow::Cow;
+
+use confique::Config;
+
+#[derive(Config)]
+struct Conf<'a> {
+    name: Option<Cow<'a, str>>,
+}
+
+fn main() {}
@@ -0,0 +1,23 @@
+use std::collections::HashMap;
+
+use confique::Config;
+
+#[derive(Config)]
+struct Array {
+    #[config(default = [1u8, 2u16])]
+    values: Vec<u16>,
+}
+
+#[derive(Config)]
+struct Nested {
+    #[config(default = [[1u8], [2, 3u16]])]
+    values: Vec<Vec<u16>>,
+}
+
+#[derive(Config)]
+struct Map {
+    #[config(default = { "a": 1u8, "b": 2u16 })]
+    values: HashMap<String, u16>,
+}
+
+fn main() {}
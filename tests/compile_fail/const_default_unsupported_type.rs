@@ -0,0 +1,11 @@
+use confique::Config;
+
+const DEFAULT_DIR: &str = "/tmp";
+
+#[derive(Config)]
+struct Conf {
+    #[config(default = const DEFAULT_DIR)]
+    dir: std::path::PathBuf,
+}
+
+fn main() {}
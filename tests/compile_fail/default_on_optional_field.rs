@@ -0,0 +1,9 @@
+use confique::Config;
+
+#[derive(Config)]
+struct Conf {
+    #[config(default = 3)]
+    field: Option<u32>,
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+use confique::Config;
+
+fn parse_flag(_: &str) -> Result<bool, std::str::ParseBoolError> {
+    Ok(true)
+}
+
+#[derive(Config)]
+struct Conf {
+    #[config(env = "FLAG", env_bool_lenient, parse_env = parse_flag)]
+    field: bool,
+}
+
+fn main() {}
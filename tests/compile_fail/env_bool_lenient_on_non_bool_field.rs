@@ -0,0 +1,9 @@
+use confique::Config;
+
+#[derive(Config)]
+struct Conf {
+    #[config(env = "FLAG", env_bool_lenient)]
+    field: u32,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use confique::Config;
+
+#[derive(Config)]
+struct Conf {
+    #[config(env_bool_lenient)]
+    field: bool,
+}
+
+fn main() {}
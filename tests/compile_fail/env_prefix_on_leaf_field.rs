@@ -0,0 +1,9 @@
+use confique::Config;
+
+#[derive(Config)]
+struct Conf {
+    #[config(env_prefix = "FOO_")]
+    field: u32,
+}
+
+fn main() {}
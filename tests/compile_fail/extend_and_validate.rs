@@ -0,0 +1,19 @@
+use confique::Config;
+
+#[derive(Config)]
+struct Inner {
+    #[config(env = "INNER_X", default = 1)]
+    x: u32,
+}
+
+fn check_inner(_: &Inner) -> Result<(), &'static str> {
+    Ok(())
+}
+
+#[derive(Config)]
+struct Conf {
+    #[config(extend, validate = check_inner)]
+    field: Inner,
+}
+
+fn main() {}
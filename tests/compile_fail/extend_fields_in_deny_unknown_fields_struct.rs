@@ -0,0 +1,16 @@
+use confique::Config;
+
+#[derive(Config)]
+struct Inner {
+    #[config(env = "INNER_X", default = 1)]
+    x: u32,
+}
+
+#[derive(Config)]
+#[config(deny_unknown_fields)]
+struct Conf {
+    #[config(extend)]
+    field: Inner,
+}
+
+fn main() {}
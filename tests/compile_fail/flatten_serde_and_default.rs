@@ -0,0 +1,9 @@
+use confique::Config;
+
+#[derive(Config)]
+struct Conf {
+    #[config(flatten_serde, default = 3)]
+    field: std::collections::HashMap<String, u32>,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use confique::Config;
+
+#[derive(Config)]
+struct Conf {
+    #[config(flatten_serde, env = "EXTRA")]
+    field: std::collections::HashMap<String, u32>,
+}
+
+fn main() {}
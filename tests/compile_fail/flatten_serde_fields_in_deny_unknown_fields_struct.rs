@@ -0,0 +1,10 @@
+use confique::Config;
+
+#[derive(Config)]
+#[config(deny_unknown_fields)]
+struct Conf {
+    #[config(flatten_serde)]
+    field: std::collections::HashMap<String, u32>,
+}
+
+fn main() {}
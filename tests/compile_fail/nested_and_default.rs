@@ -0,0 +1,15 @@
+use confique::Config;
+
+#[derive(Config)]
+struct Inner {
+    #[config(env = "INNER_X", default = 1)]
+    x: u32,
+}
+
+#[derive(Config)]
+struct Conf {
+    #[config(nested, default = 3)]
+    field: Inner,
+}
+
+fn main() {}
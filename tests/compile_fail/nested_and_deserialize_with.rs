@@ -0,0 +1,19 @@
+use confique::Config;
+
+#[derive(Config)]
+struct Inner {
+    #[config(env = "INNER_X", default = 1)]
+    x: u32,
+}
+
+fn deserialize_inner<'de, D: serde::Deserializer<'de>>(_: D) -> Result<Inner, D::Error> {
+    todo!()
+}
+
+#[derive(Config)]
+struct Conf {
+    #[config(nested, deserialize_with = deserialize_inner)]
+    field: Inner,
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+use confique::Config;
+
+#[derive(Config)]
+struct Inner {
+    #[config(env = "INNER_X", default = 1)]
+    x: u32,
+}
+
+type PartialInner = <Inner as Config>::Partial;
+
+#[derive(Config)]
+struct Conf {
+    #[config(nested, with_partial = PartialInner)]
+    field: Inner,
+}
+
+fn main() {}
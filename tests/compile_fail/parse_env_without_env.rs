@@ -0,0 +1,9 @@
+use confique::Config;
+
+#[derive(Config)]
+struct Conf {
+    #[config(parse_env = confique::env::parse::list_by_comma)]
+    field: Vec<u32>,
+}
+
+fn main() {}
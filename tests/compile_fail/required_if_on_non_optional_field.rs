@@ -0,0 +1,9 @@
+use confique::Config;
+
+#[derive(Config)]
+struct Conf {
+    #[config(required_if(true))]
+    field: u32,
+}
+
+fn main() {}
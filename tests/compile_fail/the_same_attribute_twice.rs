@@ -0,0 +1,9 @@
+use confique::Config;
+
+#[derive(Config)]
+struct Conf {
+    #[config(env = "A", env = "B")]
+    field: u32,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use confique::Config;
+
+#[derive(Config)]
+struct Conf {
+    #[config(defualt = 3)]
+    field: u32,
+}
+
+fn main() {}
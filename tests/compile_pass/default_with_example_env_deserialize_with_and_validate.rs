@@ -0,0 +1,18 @@
+use confique::Config;
+
+fn deserialize_port<'de, D: serde::Deserializer<'de>>(d: D) -> Result<u32, D::Error> {
+    serde::Deserialize::deserialize(d)
+}
+
+fn check_port(_: &u32) -> Result<(), &'static str> {
+    Ok(())
+}
+
+#[derive(Config)]
+struct Conf {
+    #[config(default = 3, example = 80, env = "PORT")]
+    #[config(deserialize_with = deserialize_port, validate = check_port)]
+    field: u32,
+}
+
+fn main() {}
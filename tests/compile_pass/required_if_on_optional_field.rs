@@ -0,0 +1,9 @@
+use confique::Config;
+
+#[derive(Config)]
+struct Conf {
+    #[config(required_if(true))]
+    field: Option<u32>,
+}
+
+fn main() {}
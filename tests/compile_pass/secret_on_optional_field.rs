@@ -0,0 +1,9 @@
+use confique::Config;
+
+#[derive(Config)]
+struct Conf {
+    #[config(secret)]
+    field: Option<String>,
+}
+
+fn main() {}
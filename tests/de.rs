@@ -0,0 +1,131 @@
+use std::time::Duration;
+
+use pretty_assertions::assert_eq;
+
+use confique::{de, Config, Partial};
+
+
+#[derive(Config)]
+#[config(partial_attr(derive(Debug)))]
+#[allow(dead_code)]
+struct Conf {
+    #[config(env = "DE_TEST_COUNT", deserialize_with = de::number_with_underscores)]
+    count: Option<u32>,
+
+    #[config(deserialize_with = de::number_with_underscores)]
+    ratio: Option<f64>,
+
+    #[config(env = "DE_TEST_SIZE", deserialize_with = de::bytesize)]
+    size: Option<u64>,
+
+    #[config(deserialize_with = de::bytesize)]
+    small_size: Option<u16>,
+
+    #[config(env = "DE_TEST_TIMEOUT", deserialize_with = de::duration_seconds)]
+    timeout: Option<Duration>,
+}
+
+type PartialConf = <Conf as Config>::Partial;
+
+fn from_toml(s: &str) -> Result<PartialConf, String> {
+    toml::from_str(s).map_err(|e| e.message().to_owned())
+}
+
+#[test]
+fn number_with_underscores_from_file() {
+    let partial = from_toml("count = 10000\nratio = 0.5").unwrap();
+    assert_eq!(partial.count, Some(10_000));
+    assert_eq!(partial.ratio, Some(0.5));
+
+    let partial = from_toml("count = \"10_000\"\nratio = \"1_000.25\"").unwrap();
+    assert_eq!(partial.count, Some(10_000));
+    assert_eq!(partial.ratio, Some(1000.25));
+
+    assert_eq!(
+        from_toml("count = \"10,000\"").unwrap_err(),
+        "invalid number '10,000': invalid digit found in string",
+    );
+    assert!(from_toml("count = true").is_err());
+}
+
+#[test]
+fn bytesize_from_file() {
+    let cases = [
+        ("size = 1024", 1024),
+        ("size = \"512\"", 512),
+        ("size = \"10B\"", 10),
+        ("size = \"64 kB\"", 64_000),
+        ("size = \"1MiB\"", 1 << 20),
+        ("size = \"1_000 mb\"", 1_000_000_000),
+        ("size = \"3 GiB\"", 3 << 30),
+        ("size = \"2TB\"", 2_000_000_000_000),
+        ("size = \"1tib\"", 1 << 40),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(from_toml(input).unwrap().size, Some(expected), "input: {input}");
+    }
+
+    assert_eq!(from_toml("size = \"1 XB\"").unwrap_err(), "invalid size in bytes: '1 XB'");
+    assert_eq!(from_toml("size = \"MiB\"").unwrap_err(), "invalid size in bytes: 'MiB'");
+    assert_eq!(from_toml("size = \"-1\"").unwrap_err(), "invalid size in bytes: '-1'");
+    assert_eq!(
+        from_toml("small_size = \"64 KiB\"").unwrap_err(),
+        "size in bytes too large: '64 KiB'",
+    );
+    assert_eq!(from_toml("small_size = \"1 KiB\"").unwrap().small_size, Some(1024));
+}
+
+#[test]
+fn duration_seconds_from_file() {
+    let cases = [
+        ("timeout = 30", Duration::from_secs(30)),
+        ("timeout = 0", Duration::ZERO),
+        ("timeout = 1.5", Duration::from_millis(1500)),
+        ("timeout = \"90\"", Duration::from_secs(90)),
+        ("timeout = \"3_600\"", Duration::from_secs(3600)),
+        ("timeout = \" 0.25 \"", Duration::from_millis(250)),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(from_toml(input).unwrap().timeout, Some(expected), "input: {input}");
+    }
+
+    assert_eq!(from_toml("timeout = -1").unwrap_err(), "invalid duration in seconds: '-1'");
+    assert_eq!(
+        from_toml("timeout = \"30s\"").unwrap_err(),
+        "invalid duration in seconds: '30s'",
+    );
+    assert_eq!(from_toml("timeout = nan").unwrap_err(), "invalid duration in seconds: 'NaN'");
+    assert!(from_toml("timeout = [30, 0]").is_err());
+}
+
+#[test]
+fn duration_defaults() {
+    #[derive(Config)]
+    struct Conf {
+        #[config(default = 30, deserialize_with = de::duration_seconds)]
+        seconds: Duration,
+
+        #[config(default = 0.5, deserialize_with = de::duration_seconds)]
+        fraction: Duration,
+
+        // Without `duration_seconds`, the `serde` representation is used.
+        #[config(default = [30, 500])]
+        serde_repr: Duration,
+    }
+
+    let conf = Conf::builder().load().unwrap();
+    assert_eq!(conf.seconds, Duration::from_secs(30));
+    assert_eq!(conf.fraction, Duration::from_millis(500));
+    assert_eq!(conf.serde_repr, Duration::new(30, 500));
+}
+
+#[test]
+fn from_env() {
+    std::env::set_var("DE_TEST_COUNT", "1_500");
+    std::env::set_var("DE_TEST_SIZE", "16 KiB");
+    std::env::set_var("DE_TEST_TIMEOUT", "45");
+    let partial = PartialConf::from_env().unwrap();
+    assert_eq!(partial.count, Some(1_500));
+    assert_eq!(partial.size, Some(16 * 1024));
+    assert_eq!(partial.timeout, Some(Duration::from_secs(45)));
+}
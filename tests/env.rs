@@ -19,6 +19,75 @@ fn enum_env() {
     assert!(matches!(conf, Ok(Conf { foo: Foo::B })));
 }
 
+#[derive(Debug, PartialEq, Deserialize)]
+enum Limit { Unlimited, Max(u32), Pair(u32, u32) }
+
+#[test]
+fn enum_with_data_env() {
+    #[derive(Config)]
+    struct Conf {
+        #[config(env = "ENUM_WITH_DATA_LIMIT")]
+        limit: Limit,
+    }
+
+    std::env::set_var("ENUM_WITH_DATA_LIMIT", "Unlimited");
+    assert_eq!(Conf::builder().env().load().unwrap().limit, Limit::Unlimited);
+
+    std::env::set_var("ENUM_WITH_DATA_LIMIT", "Max:20");
+    assert_eq!(Conf::builder().env().load().unwrap().limit, Limit::Max(20));
+
+    std::env::set_var("ENUM_WITH_DATA_LIMIT", "Pair:1,2");
+    let err = Conf::builder().env().load().err().unwrap();
+    assert_eq!(err.to_string(), "failed to deserialize value `Conf::limit` from \
+        environment variable `ENUM_WITH_DATA_LIMIT`: enum variant `Pair` is a tuple variant, \
+        which cannot be loaded from environment variables (only unit and newtype variants \
+        are supported)");
+}
+
+#[test]
+fn empty_collections() {
+    #[derive(Config)]
+    struct Conf {
+        #[config(env = "EMPTY_COLLECTIONS_PLAIN", default = ["a"])]
+        plain: Vec<String>,
+
+        #[config(
+            env = "EMPTY_COLLECTIONS_PARSED",
+            parse_env = confique::env::parse::list_by_comma,
+            default = ["b"],
+        )]
+        parsed: Vec<String>,
+
+        #[config(
+            env = "EMPTY_COLLECTIONS_OPTIONAL",
+            parse_env = confique::env::parse::list_by_comma,
+        )]
+        optional: Option<Vec<u16>>,
+    }
+
+    type PartialConf = <Conf as Config>::Partial;
+
+    std::env::set_var("EMPTY_COLLECTIONS_PLAIN", "");
+    std::env::set_var("EMPTY_COLLECTIONS_PARSED", "");
+    std::env::set_var("EMPTY_COLLECTIONS_OPTIONAL", "");
+    let partial = PartialConf::from_env().unwrap();
+    assert_eq!(partial.plain, Some(vec![]));
+    assert_eq!(partial.parsed, Some(vec![]));
+    assert_eq!(partial.optional, Some(vec![]));
+
+    // The empty lists are not replaced by the defaults.
+    let conf = Conf::builder().env().load().unwrap();
+    assert!(conf.plain.is_empty());
+    assert!(conf.parsed.is_empty());
+    assert_eq!(conf.optional, Some(vec![]));
+
+    std::env::set_var("EMPTY_COLLECTIONS_PARSED", "x,y");
+    std::env::set_var("EMPTY_COLLECTIONS_OPTIONAL", "1,2");
+    let partial = PartialConf::from_env().unwrap();
+    assert_eq!(partial.parsed, Some(vec!["x".to_owned(), "y".to_owned()]));
+    assert_eq!(partial.optional, Some(vec![1, 2]));
+}
+
 fn my_parser(s: &str) -> Result<u32, impl std::error::Error> {
     s.trim().parse()
 }
@@ -104,3 +173,188 @@ fn empty_error_is_unset() {
         validate_parse: None,
     });
 }
+
+mod env_auto {
+    use super::*;
+
+    #[derive(Config)]
+    #[config(env_prefix = "ENV_AUTO_TEST_")]
+    #[allow(dead_code)]
+    pub(crate) struct Conf {
+        #[config(env = "ENV_AUTO_TEST_LEGACY_NAME", env_auto)]
+        pub(crate) name: Option<String>,
+
+        #[config(env = "ENV_AUTO_TEST_LEGACY_LEVEL", env_auto)]
+        pub(crate) level: Option<u32>,
+
+        #[config(env_auto, parse_env = my_parser)]
+        pub(crate) count: Option<u32>,
+
+        #[config(nested)]
+        pub(crate) http: Http,
+    }
+
+    #[derive(Config)]
+    #[config(env_prefix = "IGNORED_")]
+    #[allow(dead_code)]
+    pub(crate) struct Http {
+        #[config(env_auto)]
+        pub(crate) port: Option<u16>,
+
+        #[config(env_auto)]
+        pub(crate) max_body_size: Option<u32>,
+    }
+}
+
+#[test]
+fn env_auto() {
+    use env_auto::Conf;
+    type PartialConf = <Conf as Config>::Partial;
+
+    // Nothing set
+    let partial = PartialConf::from_env().unwrap();
+    assert!(partial.is_empty());
+
+    // Derived keys, including nested fields.
+    std::env::set_var("ENV_AUTO_TEST_NAME", "auto");
+    std::env::set_var("ENV_AUTO_TEST_COUNT", " 7 ");
+    std::env::set_var("ENV_AUTO_TEST_HTTP_PORT", "8080");
+    std::env::set_var("ENV_AUTO_TEST_HTTP_MAX_BODY_SIZE", "1024");
+    std::env::set_var("IGNORED_PORT", "1");
+    let partial = PartialConf::from_env().unwrap();
+    assert_eq!(partial.name.as_deref(), Some("auto"));
+    assert_eq!(partial.count, Some(7));
+    assert_eq!(partial.http.port, Some(8080));
+    assert_eq!(partial.http.max_body_size, Some(1024));
+
+    // The explicit key has priority over the derived one.
+    std::env::set_var("ENV_AUTO_TEST_LEGACY_NAME", "legacy");
+    let partial = PartialConf::from_env().unwrap();
+    assert_eq!(partial.name.as_deref(), Some("legacy"));
+
+    // An explicit key set to an empty string that fails to deserialize is
+    // treated as unset and thus falls back to the derived key.
+    std::env::set_var("ENV_AUTO_TEST_LEGACY_LEVEL", "");
+    std::env::set_var("ENV_AUTO_TEST_LEVEL", "3");
+    let partial = PartialConf::from_env().unwrap();
+    assert_eq!(partial.level, Some(3));
+
+    // Another prefix can be specified.
+    std::env::set_var("OTHER_HTTP_PORT", "9000");
+    let partial = PartialConf::from_env_with_prefix("OTHER_").unwrap();
+    assert_eq!(partial.name.as_deref(), Some("legacy"));
+    assert_eq!(partial.http.port, Some(9000));
+
+    // The nested struct's own prefix is used when loading it as root.
+    let http = <<env_auto::Http as Config>::Partial as Partial>::from_env().unwrap();
+    assert_eq!(http.port, Some(1));
+}
+
+#[test]
+fn bool_lenient() {
+    #[derive(Config)]
+    struct Conf {
+        #[config(env = "BOOL_LENIENT_STRICT", default = true)]
+        strict: bool,
+
+        #[config(env = "BOOL_LENIENT_LENIENT", env_bool_lenient, default = true)]
+        lenient: bool,
+
+        #[config(env = "BOOL_LENIENT_OPTIONAL", env_bool_lenient)]
+        optional: Option<bool>,
+    }
+
+    std::env::set_var("BOOL_LENIENT_LENIENT", "maybe");
+    std::env::set_var("BOOL_LENIENT_OPTIONAL", "maybe");
+    let conf = Conf::builder().env().load().unwrap();
+    assert!(conf.strict);
+    assert!(conf.lenient);
+    assert_eq!(conf.optional, None);
+
+    // Recognized values are still loaded.
+    std::env::set_var("BOOL_LENIENT_LENIENT", "no");
+    std::env::set_var("BOOL_LENIENT_OPTIONAL", " TRUE ");
+    let conf = Conf::builder().env().load().unwrap();
+    assert!(!conf.lenient);
+    assert_eq!(conf.optional, Some(true));
+
+    std::env::set_var("BOOL_LENIENT_STRICT", "maybe");
+    let err = Conf::builder().env().load().err().unwrap();
+    assert_eq!(err.to_string(), "failed to deserialize value `Conf::strict` from \
+        environment variable `BOOL_LENIENT_STRICT`: invalid value for bool: 'maybe'");
+}
+
+mod multi_instance {
+    use super::*;
+
+    #[derive(Config)]
+    #[config(env_prefix = "MULTI_")]
+    pub struct Conf {
+        #[config(nested, env_prefix = "A_")]
+        pub a: Endpoint,
+
+        #[config(nested, env_prefix = "B_")]
+        pub b: Endpoint,
+
+        #[config(nested)]
+        pub fallback: Endpoint,
+    }
+
+    #[derive(Config)]
+    pub struct Endpoint {
+        #[config(env_auto)]
+        pub host: String,
+
+        #[config(env_auto, default = 80)]
+        pub port: u16,
+    }
+}
+
+#[test]
+fn nested_env_prefix() {
+    use multi_instance::Conf;
+
+    std::env::set_var("MULTI_A_HOST", "a.example.com");
+    std::env::set_var("MULTI_B_HOST", "b.example.com");
+    std::env::set_var("MULTI_B_PORT", "8080");
+    std::env::set_var("MULTI_FALLBACK_HOST", "localhost");
+    let conf = Conf::builder().env().load().unwrap();
+    assert_eq!(conf.a.host, "a.example.com");
+    assert_eq!(conf.a.port, 80);
+    assert_eq!(conf.b.host, "b.example.com");
+    assert_eq!(conf.b.port, 8080);
+    assert_eq!(conf.fallback.host, "localhost");
+}
+
+#[test]
+fn int_auto_radix() {
+    #[derive(Config)]
+    struct Conf {
+        #[config(env = "AUTO_RADIX_MASK", parse_env = confique::env::parse::int_auto_radix)]
+        mask: u32,
+        #[config(env = "AUTO_RADIX_MODE", parse_env = confique::env::parse::int_auto_radix)]
+        mode: Option<u16>,
+        #[config(env = "AUTO_RADIX_FLAGS", parse_env = confique::env::parse::int_auto_radix)]
+        flags: u8,
+        #[config(env = "AUTO_RADIX_OFFSET", parse_env = confique::env::parse::int_auto_radix)]
+        offset: i64,
+    }
+
+    std::env::set_var("AUTO_RADIX_MASK", "0xFFFF0000");
+    std::env::set_var("AUTO_RADIX_MODE", "0o755");
+    std::env::set_var("AUTO_RADIX_FLAGS", "0b1001");
+    std::env::set_var("AUTO_RADIX_OFFSET", "-1000");
+    let conf = Conf::builder().env().load().unwrap();
+    assert_eq!(conf.mask, 0xFFFF0000);
+    assert_eq!(conf.mode, Some(0o755));
+    assert_eq!(conf.flags, 0b1001);
+    assert_eq!(conf.offset, -1000);
+
+    std::env::set_var("AUTO_RADIX_FLAGS", "0x1FF");
+    let err = Conf::builder().env().load().err().unwrap();
+    assert_eq!(
+        err.display_chain().to_string(),
+        "failed to parse environment variable `AUTO_RADIX_FLAGS` into field `Conf::flags`: \
+            number too large to fit in target type",
+    );
+}
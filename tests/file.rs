@@ -0,0 +1,100 @@
+use pretty_assertions::assert_eq;
+
+use confique::Config;
+
+
+#[test]
+fn base_dir() {
+    #[derive(Config)]
+    struct Conf {
+        #[config(default = 1)]
+        a: u32,
+        #[config(default = 1)]
+        b: u32,
+        #[config(default = 1)]
+        c: u32,
+    }
+
+    let dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("base_dir");
+    std::fs::create_dir_all(dir.join("sub")).unwrap();
+    std::fs::write(dir.join("first.toml"), "a = 2").unwrap();
+    std::fs::write(dir.join("sub/second.toml"), "b = 3").unwrap();
+    std::fs::write(dir.join("absolute.toml"), "c = 4").unwrap();
+
+    let conf = Conf::builder()
+        // Not affected as it's added before `base_dir`: resolved against the
+        // working directory, where that file doesn't exist.
+        .file("first.toml")
+        .base_dir(&dir)
+        .file("first.toml")
+        .file("sub/second.toml")
+        .base_dir("/does-not-exist")
+        .file(dir.join("absolute.toml"))
+        .load()
+        .unwrap();
+    assert_eq!((conf.a, conf.b, conf.c), (2, 3, 4));
+}
+
+#[test]
+fn embedded() {
+    use confique::FileFormat;
+
+    #[derive(Config)]
+    struct Conf {
+        #[config(default = 8080)]
+        port: u16,
+        #[config(default = "localhost")]
+        host: String,
+        name: String,
+    }
+
+    const EMBEDDED: &str = "port = 3000\nname = \"embedded\"\n";
+
+    // Embedded values are overridden by earlier sources, but override the
+    // defaults of the derive.
+    let conf = Conf::builder()
+        .overrides(["name=override"])
+        .embedded(EMBEDDED, FileFormat::Toml)
+        .load()
+        .unwrap();
+    assert_eq!(conf.port, 3000);
+    assert_eq!(conf.host, "localhost");
+    assert_eq!(conf.name, "override");
+
+    let err = Conf::builder().embedded("port = ", FileFormat::Toml).load().err().unwrap();
+    assert_eq!(err.to_string(), "failed to deserialize configuration from embedded configuration");
+}
+
+#[test]
+fn empty_required_file() {
+    use confique::File;
+
+    #[derive(Config)]
+    struct Conf {
+        #[config(default = 8080)]
+        port: u16,
+    }
+
+    let dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR"));
+    let path = dir.join("empty_required_file.toml");
+    std::fs::write(&path, " \n\t\n").unwrap();
+
+    let err = File::new(&path).unwrap().required().load::<<Conf as Config>::Partial>()
+        .err()
+        .unwrap();
+    assert_eq!(err.to_string(), format!(
+        "required configuration file is empty: '{}' (it has to contain configuration values)",
+        path.display(),
+    ));
+
+    // Allowed if configured or if the file is not required.
+    let partial = File::new(&path).unwrap().required().allow_empty()
+        .load::<<Conf as Config>::Partial>()
+        .unwrap();
+    assert_eq!(partial.port, None);
+    assert_eq!(Conf::builder().file(&path).load().unwrap().port, 8080);
+
+    // Comments count as content.
+    std::fs::write(&path, "# port = 80\n").unwrap();
+    assert!(File::new(&path).unwrap().required().load::<<Conf as Config>::Partial>().is_ok());
+}
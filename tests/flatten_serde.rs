@@ -0,0 +1,103 @@
+use pretty_assertions::assert_eq;
+use serde::Deserialize;
+
+use confique::{Config, Partial};
+
+
+/// Stand-in for a settings type of some external library.
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct LibSettings {
+    threads: u32,
+    #[serde(default)]
+    verbose: bool,
+}
+
+/// Settings type where all fields have defaults.
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct Defaulted {
+    #[serde(default = "default_retries")]
+    retries: u32,
+}
+
+fn default_retries() -> u32 {
+    3
+}
+
+mod conf {
+    use super::*;
+
+    #[derive(Config)]
+    #[config(partial_attr(derive(Debug, PartialEq)))]
+    pub struct Conf {
+        #[config(default = 8080)]
+        pub port: u16,
+
+        #[config(flatten_serde)]
+        pub lib: LibSettings,
+
+        #[config(nested)]
+        pub sub: Sub,
+    }
+
+    #[derive(Config)]
+    #[config(partial_attr(derive(Debug, PartialEq)))]
+    pub struct Sub {
+        #[config(flatten_serde)]
+        pub defaulted: Defaulted,
+
+        #[config(flatten_serde)]
+        pub optional: Option<LibSettings>,
+    }
+}
+
+use conf::Conf;
+type PartialConf = <Conf as Config>::Partial;
+
+fn load(toml: &str) -> PartialConf {
+    toml::from_str(toml).unwrap()
+}
+
+#[test]
+fn flatten_serde() {
+    let partial = load("port = 80\nthreads = 4");
+    assert_eq!(partial.port, Some(80));
+    assert_eq!(partial.lib, Some(LibSettings { threads: 4, verbose: false }));
+    assert_eq!(partial.sub.defaulted, None);
+    assert_eq!(partial.sub.optional, None);
+
+    let conf = Conf::builder()
+        .preloaded(partial)
+        .preloaded(load("threads = 8\nverbose = true"))
+        .load()
+        .unwrap();
+    assert_eq!(conf.port, 80);
+    assert_eq!(conf.lib, LibSettings { threads: 4, verbose: false });
+    assert_eq!(conf.sub.defaulted, Defaulted { retries: 3 });
+    assert_eq!(conf.sub.optional, None);
+
+    let conf = Conf::builder()
+        .preloaded(load("threads = 1\nverbose = true\n[sub]\nretries = 5\nthreads = 2"))
+        .load()
+        .unwrap();
+    assert_eq!(conf.lib, LibSettings { threads: 1, verbose: true });
+    assert_eq!(conf.sub.defaulted, Defaulted { retries: 5 });
+    assert_eq!(conf.sub.optional, Some(LibSettings { threads: 2, verbose: false }));
+}
+
+#[test]
+fn flatten_serde_all_or_nothing() {
+    // Keys of a flattened type are not layered individually: `threads` is not
+    // taken from the lower priority layer.
+    let err = toml::from_str::<PartialConf>("verbose = true").unwrap_err();
+    assert!(err.to_string().contains("missing field `threads`"), "{err}");
+
+    // Missing required values are reported with the field's path.
+    let err = Conf::builder().preloaded(PartialConf::empty()).load().err().unwrap();
+    assert_eq!(err.to_string(), "required configuration value is missing: 'lib'");
+}
+
+#[test]
+fn flatten_serde_meta() {
+    let field = Conf::META.find("lib").unwrap();
+    assert!(field.hidden_in_template);
+}
@@ -27,8 +27,13 @@ fn simple() {
             meta::Field {
                 name: "cat",
                 doc: &[" Doc comment for cat."],
+                hidden_in_template: false,
                 kind: meta::FieldKind::Leaf {
                     env: None,
+                    has_parse_env: false,
+                    has_deserialize_with: false,
+                    example: None,
+                    secret: false,
                     kind: meta::LeafKind::Required {
                         default: Some(meta::Expr::Integer(meta::Integer::U32(8080))),
                     },
@@ -37,14 +42,20 @@ fn simple() {
             meta::Field {
                 name: "dog",
                 doc: &[" Doc comment for dog."],
+                hidden_in_template: false,
                 kind: meta::FieldKind::Leaf {
                     env: None,
+                    has_parse_env: false,
+                    has_deserialize_with: false,
+                    example: None,
+                    secret: false,
                     kind: meta::LeafKind::Required {
                         default: None,
                     },
                 },
             },
         ],
+        preambles: meta::Preambles::NONE,
     });
 
     let def = <Animals as Config>::Partial::default_values();
@@ -129,14 +140,20 @@ fn full() {
             meta::Field {
                 name: "app_name",
                 doc: &[" Leaf field on top level struct."],
+                hidden_in_template: false,
                 kind: meta::FieldKind::Leaf {
                     env: None,
+                    has_parse_env: false,
+                    has_deserialize_with: false,
+                    example: None,
+                    secret: false,
                     kind: meta::LeafKind::Required { default: None },
                 },
             },
             meta::Field {
                 name: "normal",
                 doc: &[],
+                hidden_in_template: false,
                 kind: meta::FieldKind::Nested {
                     meta: &meta::Meta {
                         name: "NormalTest",
@@ -145,16 +162,26 @@ fn full() {
                             meta::Field {
                                 name: "required",
                                 doc: &[],
+                                hidden_in_template: false,
                                 kind: meta::FieldKind::Leaf {
                                     env: None,
+                                    has_parse_env: false,
+                                    has_deserialize_with: false,
+                                    example: None,
+                                    secret: false,
                                     kind: meta::LeafKind::Required { default: None },
                                 },
                             },
                             meta::Field {
                                 name: "with_default",
                                 doc: &[],
+                                hidden_in_template: false,
                                 kind: meta::FieldKind::Leaf {
                                     env: None,
+                                    has_parse_env: false,
+                                    has_deserialize_with: false,
+                                    example: None,
+                                    secret: false,
                                     kind: meta::LeafKind::Required {
                                         default: Some(meta::Expr::Str("127.0.0.1")),
                                     },
@@ -163,18 +190,25 @@ fn full() {
                             meta::Field {
                                 name: "optional",
                                 doc: &[],
+                                hidden_in_template: false,
                                 kind: meta::FieldKind::Leaf {
                                     env: None,
+                                    has_parse_env: false,
+                                    has_deserialize_with: false,
+                                    example: None,
+                                    secret: false,
                                     kind: meta::LeafKind::Optional,
                                 },
                             },
                         ],
+                        preambles: meta::Preambles::NONE,
                     },
                 },
             },
             meta::Field {
                 name: "deserialize_with",
                 doc: &[],
+                hidden_in_template: false,
                 kind: meta::FieldKind::Nested {
                     meta: &meta::Meta {
                         name: "DeserializeWithTest",
@@ -183,16 +217,26 @@ fn full() {
                             meta::Field {
                                 name: "required",
                                 doc: &[],
+                                hidden_in_template: false,
                                 kind: meta::FieldKind::Leaf {
                                     env: None,
+                                    has_parse_env: false,
+                                    has_deserialize_with: true,
+                                    example: None,
+                                    secret: false,
                                     kind: meta::LeafKind::Required { default: None },
                                 },
                             },
                             meta::Field {
                                 name: "with_default",
                                 doc: &[],
+                                hidden_in_template: false,
                                 kind: meta::FieldKind::Leaf {
                                     env: None,
+                                    has_parse_env: false,
+                                    has_deserialize_with: true,
+                                    example: None,
+                                    secret: false,
                                     kind: meta::LeafKind::Required {
                                         default: Some(meta::Expr::Str("peter")),
                                     },
@@ -201,26 +245,38 @@ fn full() {
                             meta::Field {
                                 name: "optional",
                                 doc: &[],
+                                hidden_in_template: false,
                                 kind: meta::FieldKind::Leaf {
                                     env: None,
+                                    has_parse_env: false,
+                                    has_deserialize_with: true,
+                                    example: None,
+                                    secret: false,
                                     kind: meta::LeafKind::Optional,
                                 },
                             },
                             meta::Field {
                                 name: "with_env",
                                 doc: &[],
+                                hidden_in_template: false,
                                 kind: meta::FieldKind::Leaf {
                                     env: Some("ENV_TEST_FULL_0"),
+                                    has_parse_env: false,
+                                    has_deserialize_with: true,
+                                    example: None,
+                                    secret: false,
                                     kind: meta::LeafKind::Required { default: None },
                                 },
                             },
-                        ]
+                        ],
+                        preambles: meta::Preambles::NONE,
                     },
                 },
             },
             meta::Field {
                 name: "env",
                 doc: &[" Doc comment on nested."],
+                hidden_in_template: false,
                 kind: meta::FieldKind::Nested {
                     meta: &meta::Meta {
                         name: "EnvTest",
@@ -229,16 +285,26 @@ fn full() {
                             meta::Field {
                                 name: "required",
                                 doc: &[],
+                                hidden_in_template: false,
                                 kind: meta::FieldKind::Leaf {
                                     env: Some("ENV_TEST_FULL_1"),
+                                    has_parse_env: false,
+                                    has_deserialize_with: false,
+                                    example: None,
+                                    secret: false,
                                     kind: meta::LeafKind::Required { default: None },
                                 },
                             },
                             meta::Field {
                                 name: "with_default",
                                 doc: &[],
+                                hidden_in_template: false,
                                 kind: meta::FieldKind::Leaf {
                                     env: Some("ENV_TEST_FULL_2"),
+                                    has_parse_env: false,
+                                    has_deserialize_with: false,
+                                    example: None,
+                                    secret: false,
                                     kind: meta::LeafKind::Required {
                                         default: Some(
                                             meta::Expr::Integer(meta::Integer::U16(8080))
@@ -249,24 +315,36 @@ fn full() {
                             meta::Field {
                                 name: "optional",
                                 doc: &[],
+                                hidden_in_template: false,
                                 kind: meta::FieldKind::Leaf {
                                     env: Some("ENV_TEST_FULL_3"),
+                                    has_parse_env: false,
+                                    has_deserialize_with: false,
+                                    example: None,
+                                    secret: false,
                                     kind: meta::LeafKind::Optional,
                                 },
                             },
                             meta::Field {
                                 name: "env_collection",
                                 doc: &[],
+                                hidden_in_template: false,
                                 kind: meta::FieldKind::Leaf {
                                     env: Some("ENV_TEST_FULL_4"),
+                                    has_parse_env: true,
+                                    has_deserialize_with: false,
+                                    example: None,
+                                    secret: false,
                                     kind: meta::LeafKind::Required { default: None },
                                 },
                             },
                         ],
+                        preambles: meta::Preambles::NONE,
                     },
                 },
             },
         ],
+        preambles: meta::Preambles::NONE,
     });
 
     let def = <Conf as Config>::Partial::default_values();
@@ -318,3 +396,627 @@ fn empty_array_and_map() {
         dog: HashMap<u32, f32>,
     }
 }
+
+mod generic {
+    use super::*;
+
+    #[derive(Config)]
+    pub(crate) struct Outer<T, const N: usize>
+    where
+        T: std::fmt::Debug,
+    {
+        #[config(env = "GENERIC_TEST_VALUE")]
+        pub(crate) value: T,
+
+        #[config(default = [1, 2, 3])]
+        pub(crate) array: [u8; N],
+
+        #[config(validate(!list.is_empty(), "list must not be empty"))]
+        pub(crate) list: Vec<T>,
+
+        #[config(nested)]
+        pub(crate) inner: Inner<T>,
+    }
+
+    #[derive(Config)]
+    pub(crate) struct Inner<T> {
+        #[config(deserialize_with = deserialize_dummy)]
+        pub(crate) dummy: Dummy,
+
+        pub(crate) extra: Option<T>,
+    }
+}
+
+#[test]
+fn generic() {
+    use generic::*;
+
+    type Partial = <Outer<u32, 3> as Config>::Partial;
+    type InnerPartial = <Inner<u32> as Config>::Partial;
+
+    assert_eq!(Outer::<u32, 3>::META.fields[3].name, "inner");
+    assert_eq!(Partial::default_values().array, Some([1, 2, 3]));
+
+    std::env::set_var("GENERIC_TEST_VALUE", "27");
+    let conf = Outer::<u32, 3>::builder()
+        .env()
+        .preloaded(Partial {
+            value: None,
+            array: None,
+            list: Some(vec![1, 2]),
+            inner: InnerPartial {
+                dummy: Some(Dummy("foo".into())),
+                extra: Some(7),
+            },
+        })
+        .load()
+        .unwrap();
+
+    assert_eq!(conf.value, 27);
+    assert_eq!(conf.array, [1, 2, 3]);
+    assert_eq!(conf.list, vec![1, 2]);
+    assert_eq!(conf.inner.dummy, Dummy("foo".into()));
+    assert_eq!(conf.inner.extra, Some(7));
+
+    let err = Outer::<u32, 3>::builder()
+        .preloaded(Partial {
+            value: Some(1),
+            array: None,
+            list: None,
+            inner: InnerPartial::empty(),
+        })
+        .load()
+        .err()
+        .unwrap();
+    assert_eq!(err.to_string(), "required configuration value is missing: 'list'");
+}
+
+#[test]
+fn required_if() {
+    #[derive(Config)]
+    #[config(validate = Self::validate)]
+    struct Conf {
+        #[config(default = false)]
+        tls_enabled: bool,
+
+        #[config(required_if(*tls_enabled))]
+        tls_cert: Option<PathBuf>,
+
+        #[config(required_if(tls_cert.is_some() && *tls_enabled))]
+        tls_key: Option<PathBuf>,
+    }
+
+    impl Conf {
+        fn validate(&self) -> Result<(), &'static str> {
+            if self.tls_enabled && self.tls_cert.is_none() {
+                unreachable!("`required_if` is checked before validation");
+            }
+            Ok(())
+        }
+    }
+
+    type Partial = <Conf as Config>::Partial;
+
+    let conf = Conf::builder().load().unwrap();
+    assert_eq!(conf.tls_cert, None);
+
+    let err = Conf::builder()
+        .preloaded(Partial { tls_enabled: Some(true), tls_cert: None, tls_key: None })
+        .load()
+        .err()
+        .unwrap();
+    assert_eq!(err.to_string(), "required configuration value is missing: 'tls_cert'");
+
+    let err = Conf::builder()
+        .preloaded(Partial {
+            tls_enabled: Some(true),
+            tls_cert: Some("cert.pem".into()),
+            tls_key: None,
+        })
+        .load()
+        .err()
+        .unwrap();
+    assert_eq!(err.to_string(), "required configuration value is missing: 'tls_key'");
+
+    let conf = Conf::builder()
+        .preloaded(Partial {
+            tls_enabled: Some(true),
+            tls_cert: Some("cert.pem".into()),
+            tls_key: Some("key.pem".into()),
+        })
+        .load()
+        .unwrap();
+    assert_eq!(conf.tls_key, Some("key.pem".into()));
+}
+
+mod flatten {
+    use super::*;
+    use serde::Serialize;
+
+    #[derive(Debug, Deserialize, Serialize)]
+    pub enum Mode { Fast, Limit(u32) }
+
+    #[derive(Config)]
+    #[config(partial_attr(derive(Serialize)))]
+    pub struct Conf {
+        pub name: String,
+        pub mode: Option<Mode>,
+
+        #[config(nested)]
+        pub http: Http,
+    }
+
+    #[derive(Config)]
+    #[config(partial_attr(derive(Serialize)))]
+    #[allow(dead_code)]
+    pub struct Http {
+        #[config(default = 8080)]
+        pub port: u16,
+        pub bind: Option<IpAddr>,
+        #[config(default = { "x-user": ["a", "b"] })]
+        pub headers: HashMap<String, Vec<String>>,
+    }
+}
+
+#[test]
+fn flatten_partial() {
+    use flatten::{Conf, Mode};
+    type PartialConf = <Conf as Config>::Partial;
+
+    let flatten = |p: &PartialConf| {
+        confique::flatten_partial(p).unwrap().into_iter().collect::<Vec<_>>()
+    };
+    let entries = |e: &[(&str, &str)]| {
+        e.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect::<Vec<_>>()
+    };
+
+    assert_eq!(flatten(&PartialConf::empty()), []);
+    assert_eq!(flatten(&PartialConf::default_values()), entries(&[
+        ("http.headers", r#"{"x-user": ["a", "b"]}"#),
+        ("http.port", "8080"),
+    ]));
+
+    let mut partial = PartialConf::empty();
+    partial.name = Some("Peter \"P\"".into());
+    partial.mode = Some(Mode::Limit(3));
+    partial.http.bind = Some(IpAddr::from([127, 0, 0, 1]));
+    assert_eq!(flatten(&partial), entries(&[
+        ("http.bind", "127.0.0.1"),
+        ("mode", "Limit(3)"),
+        ("name", "Peter \"P\""),
+    ]));
+
+    partial.mode = Some(Mode::Fast);
+    assert_eq!(confique::flatten_partial(&partial).unwrap()["mode"], "Fast");
+}
+
+mod semantic_eq {
+    use super::*;
+
+    #[derive(Config)]
+    pub(crate) struct Conf {
+        #[config(default = "info")]
+        pub(crate) level: String,
+        pub(crate) name: Option<String>,
+
+        #[config(nested)]
+        pub(crate) http: Http,
+    }
+
+    #[derive(Config)]
+    #[allow(dead_code)]
+    pub(crate) struct Http {
+        #[config(default = 8080)]
+        pub(crate) port: u16,
+    }
+
+    // Field types not implementing `PartialEq` don't prevent the derive.
+    #[derive(Debug, serde::Deserialize)]
+    pub(crate) struct NoEq;
+
+    #[derive(Config)]
+    #[allow(dead_code)]
+    pub(crate) struct WithoutEq {
+        pub(crate) value: Option<NoEq>,
+    }
+}
+
+#[test]
+fn semantic_eq() {
+    use semantic_eq::Conf;
+    type Partial = <Conf as Config>::Partial;
+
+    let empty = Partial::empty();
+    assert!(empty.semantic_eq(&Partial::empty()));
+    assert!(Partial::default_values().semantic_eq(&empty));
+
+    let mut a = Partial::empty();
+    a.level = Some("info".into());
+    a.http.port = Some(8080);
+    assert!(a.semantic_eq(&empty));
+    assert!(empty.semantic_eq(&a));
+
+    a.http.port = Some(9000);
+    assert!(!a.semantic_eq(&empty));
+
+    let mut b = Partial::default_values();
+    b.http.port = Some(9000);
+    assert!(a.semantic_eq(&b));
+
+    b.name = Some("peter".into());
+    assert!(!a.semantic_eq(&b));
+    a.name = Some("peter".into());
+    assert!(a.semantic_eq(&b));
+}
+
+mod with_partial {
+    use std::time::Duration;
+    use confique::{meta::Meta, Error, FieldPartial};
+    use super::*;
+
+    #[derive(Config)]
+    pub struct Conf {
+        #[config(with_partial = PartialTimeout)]
+        pub timeout: Duration,
+    }
+
+    /// The settings `Duration` is built from.
+    #[derive(Config)]
+    pub struct Timeout {
+        #[config(default = 30)]
+        secs: u64,
+        millis: u32,
+    }
+
+    pub type PartialTimeout = <Timeout as Config>::Partial;
+
+    impl FieldPartial for PartialTimeout {
+        type Value = Duration;
+        const META: Meta = Timeout::META;
+
+        fn into_value(self) -> Result<Duration, Error> {
+            let t = Timeout::from_partial(self)?;
+            Ok(Duration::from_secs(t.secs) + Duration::from_millis(t.millis.into()))
+        }
+    }
+}
+
+#[test]
+fn with_partial() {
+    use with_partial::{Conf, PartialTimeout};
+    type Partial = <Conf as Config>::Partial;
+
+    assert_eq!(Conf::META.fields[0].name, "timeout");
+    assert!(matches!(
+        Conf::META.fields[0].kind,
+        meta::FieldKind::Nested { meta } if meta.name == "Timeout",
+    ));
+
+    // Values from different layers are merged.
+    let mut high = Partial::empty();
+    high.timeout.millis = Some(500);
+    let mut low = Partial::empty();
+    low.timeout.secs = Some(2);
+    low.timeout.millis = Some(1);
+    let conf = Conf::builder().preloaded(high).preloaded(low).load().unwrap();
+    assert_eq!(conf.timeout, std::time::Duration::from_millis(2500));
+
+    // Defaults are used.
+    let mut partial = Partial::empty();
+    partial.timeout = PartialTimeout { secs: None, millis: Some(0) };
+    let conf = Conf::builder().preloaded(partial).load().unwrap();
+    assert_eq!(conf.timeout, std::time::Duration::from_secs(30));
+
+    // Missing values are reported with the full path.
+    let err = Conf::builder().load().err().unwrap();
+    assert_eq!(err.to_string(), "required configuration value is missing: 'timeout.millis'");
+}
+
+mod const_defaults {
+    use super::*;
+
+    pub const PORT: u16 = 8080;
+    pub const RATIO: f32 = 0.5;
+    pub const NAME: &str = "peter";
+
+    pub struct Limits;
+    impl Limits {
+        pub const MAX: u64 = 1 << 40;
+        pub const STRICT: bool = true;
+    }
+
+    #[derive(Config)]
+    pub struct Conf {
+        #[config(default = const PORT)]
+        pub port: u16,
+        #[config(default = const RATIO)]
+        pub ratio: f32,
+        #[config(default = const NAME)]
+        pub name: String,
+        #[config(default = const Limits::MAX)]
+        pub max: u64,
+        #[config(default = const Limits::STRICT)]
+        pub strict: bool,
+        #[config(default = const Self::TIMEOUT)]
+        pub timeout: u32,
+        #[config(default = const self::PORT)]
+        pub fallback_port: u16,
+    }
+
+    impl Conf {
+        pub const TIMEOUT: u32 = 30;
+    }
+}
+
+#[test]
+fn const_defaults() {
+    use const_defaults::Conf;
+
+    let conf = Conf::builder().load().unwrap();
+    assert_eq!(conf.port, 8080);
+    assert_eq!(conf.ratio, 0.5);
+    assert_eq!(conf.name, "peter");
+    assert_eq!(conf.max, 1 << 40);
+    assert!(conf.strict);
+    assert_eq!(conf.timeout, 30);
+    assert_eq!(conf.fallback_port, 8080);
+
+    let defaults = Conf::META.fields.iter()
+        .map(|f| match f.kind {
+            meta::FieldKind::Leaf { kind: meta::LeafKind::Required { default }, .. } => default,
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(defaults, [
+        Some(meta::Expr::Integer(meta::Integer::U16(8080))),
+        Some(meta::Expr::Float(meta::Float::F32(0.5))),
+        Some(meta::Expr::Str("peter")),
+        Some(meta::Expr::Integer(meta::Integer::U64(1 << 40))),
+        Some(meta::Expr::Bool(true)),
+        Some(meta::Expr::Integer(meta::Integer::U32(30))),
+        Some(meta::Expr::Integer(meta::Integer::U16(8080))),
+    ]);
+}
+
+mod setters {
+    use super::*;
+
+    #[derive(Config)]
+    pub struct Conf {
+        pub name: String,
+        pub port: Option<u16>,
+        // The setter must not shadow `Partial::empty`.
+        pub empty: bool,
+        #[config(nested)]
+        pub http: Http,
+    }
+
+    #[derive(Config)]
+    pub struct Http {
+        pub bind: IpAddr,
+        #[config(default = true)]
+        pub enabled: bool,
+    }
+}
+
+#[test]
+fn setters() {
+    use setters::*;
+
+    type PartialConf = <Conf as Config>::Partial;
+    type PartialHttp = <Http as Config>::Partial;
+
+    let partial = PartialConf::empty()
+        .set_name("peter")
+        .set_port(8080u16)
+        .set_empty(true)
+        .set_http(PartialHttp::empty().set_bind([127, 0, 0, 1]));
+    assert_eq!(partial.name.as_deref(), Some("peter"));
+    assert_eq!(partial.port, Some(8080));
+    assert_eq!(partial.http.enabled, None);
+    assert!(!partial.is_empty());
+
+    let conf = Conf::builder().preloaded(partial).load().unwrap();
+    assert_eq!(conf.name, "peter");
+    assert_eq!(conf.port, Some(8080));
+    assert!(conf.empty);
+    assert_eq!(conf.http.bind, IpAddr::from([127, 0, 0, 1]));
+    assert!(conf.http.enabled);
+}
+
+mod extend {
+    use super::*;
+
+    #[derive(Config)]
+    pub struct Base {
+        #[config(default = 80)]
+        pub port: u16,
+        #[config(env_auto)]
+        pub host: String,
+        #[config(nested)]
+        pub log: Log,
+    }
+
+    #[derive(Config)]
+    pub struct Log {
+        #[config(default = "info")]
+        pub level: String,
+    }
+
+    #[derive(Config)]
+    #[config(env_prefix = "EXTEND_")]
+    pub struct Service {
+        #[config(extend(port = 8080))]
+        pub base: Base,
+        pub name: String,
+    }
+
+    #[derive(Config)]
+    pub struct App {
+        #[config(nested)]
+        pub service: Service,
+    }
+}
+
+#[test]
+fn extend() {
+    use extend::*;
+
+    let names = Service::META.fields.iter().map(|f| f.name).collect::<Vec<_>>();
+    assert_eq!(names, ["port", "host", "log", "name"]);
+    assert!(matches!(
+        Service::META.find("port").unwrap().kind,
+        meta::FieldKind::Leaf {
+            kind: meta::LeafKind::Required {
+                default: Some(meta::Expr::Integer(meta::Integer::I32(8080))),
+            },
+            ..
+        },
+    ));
+    assert!(Service::META.find("log.level").is_some());
+
+    // Fields of the base are specified without prefix, the overridden default
+    // is used and the base's default is unaffected.
+    std::env::set_var("EXTEND_HOST", "example.com");
+    let conf = Service::builder()
+        .env()
+        .overrides(["name=foo"])
+        .load()
+        .unwrap();
+    assert_eq!(conf.name, "foo");
+    assert_eq!(conf.base.port, 8080);
+    assert_eq!(conf.base.host, "example.com");
+    assert_eq!(conf.base.log.level, "info");
+    assert_eq!(<Base as Config>::Partial::default_values().port, Some(80));
+
+    type PartialService = <Service as Config>::Partial;
+    type PartialBase = <Base as Config>::Partial;
+    let conf = Service::builder()
+        .env()
+        .preloaded(PartialService::empty().set_name("foo").set_base(PartialBase::empty().set_port(3000u16)))
+        .load()
+        .unwrap();
+    assert_eq!(conf.base.port, 3000);
+    assert_eq!(conf.base.log.level, "info");
+
+    // Missing values are reported without the name of the `extend` field.
+    std::env::remove_var("EXTEND_HOST");
+    let err = Service::builder().overrides(["name=foo"]).load().err().unwrap();
+    assert_eq!(err.to_string(), "required configuration value is missing: 'host'");
+
+    // String-based sources cannot set fields of the base, as they are
+    // deserialized via `serde(flatten)`.
+    for path in ["port=1", "log.level=debug"] {
+        let err = Service::builder().overrides(["name=foo", path]).load().err().unwrap();
+        let path = path.split_once('=').unwrap().0;
+        assert_eq!(err.to_string(), format!(
+            "cannot load '{path}' from overrides: values of fields included via \
+                `#[config(extend)]` can only be loaded from files and environment variables",
+        ));
+    }
+
+    // Also when the struct with the `extend` field is nested.
+    let err = App::builder().overrides(["service.log.level=debug"]).load().err().unwrap();
+    assert!(err.to_string().starts_with("cannot load 'service.log.level' from overrides"));
+    let err = App::builder().overrides(["service.name=foo"]).load().err().unwrap();
+    assert_eq!(err.to_string(), "required configuration value is missing: 'service.host'");
+}
+
+mod examples {
+    use super::*;
+
+    #[derive(Config)]
+    pub struct Conf {
+        #[config(example = "postgres://localhost/app")]
+        pub database_url: Option<String>,
+
+        #[config(default = 8080, example = 80)]
+        pub port: u16,
+
+        #[config(nested)]
+        pub limits: Limits,
+    }
+
+    #[derive(Config)]
+    #[allow(dead_code)]
+    pub struct Limits {
+        #[config(example = [1.5, 3.0])]
+        pub backoff: Vec<f32>,
+
+        pub max: u32,
+    }
+}
+
+#[test]
+fn examples() {
+    use examples::Conf;
+
+    let examples = Conf::META.leaf_fields().into_iter()
+        .map(|(path, f)| match f.kind {
+            meta::FieldKind::Leaf { example, .. } => (path, example),
+            _ => unreachable!(),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(examples, [
+        ("database_url".into(), Some(meta::Expr::Str("postgres://localhost/app"))),
+        ("port".into(), Some(meta::Expr::Integer(meta::Integer::U16(80)))),
+        ("limits.backoff".into(), Some(meta::Expr::Array(&[
+            meta::Expr::Float(meta::Float::F32(1.5)),
+            meta::Expr::Float(meta::Float::F32(3.0)),
+        ]))),
+        ("limits.max".into(), None),
+    ]);
+
+    // Examples are not used as values.
+    assert!(<Conf as Config>::Partial::default_values().database_url.is_none());
+    assert_eq!(<Conf as Config>::Partial::default_values().port, Some(8080));
+}
+
+mod float_defaults {
+    use super::*;
+
+    #[derive(Config)]
+    pub struct Conf {
+        #[config(default = -1.5)]
+        pub negative: f64,
+        #[config(default = 1e-9)]
+        pub tiny: f64,
+        #[config(default = -2.5e10)]
+        pub negative_exp: f64,
+        #[config(default = 1e20)]
+        pub huge: f64,
+        #[config(default = -3.0e-4f32)]
+        pub suffixed: f32,
+        #[config(default = [-0.5, 1E3])]
+        pub list: Vec<f64>,
+        #[config(default = { "a": -1e-3 })]
+        pub map: HashMap<String, f32>,
+    }
+}
+
+#[test]
+fn float_defaults() {
+    use float_defaults::Conf;
+
+    let conf = Conf::builder().load().unwrap();
+    assert_eq!(conf.negative, -1.5);
+    assert_eq!(conf.tiny, 1e-9);
+    assert_eq!(conf.negative_exp, -2.5e10);
+    assert_eq!(conf.huge, 1e20);
+    assert_eq!(conf.suffixed, -3.0e-4);
+    assert_eq!(conf.list, [-0.5, 1000.0]);
+    assert_eq!(conf.map, HashMap::from([("a".into(), -1e-3)]));
+
+    let default = |path| match Conf::META.find(path).unwrap().kind {
+        meta::FieldKind::Leaf { kind: meta::LeafKind::Required { default }, .. } => default,
+        _ => None,
+    };
+    assert_eq!(default("negative"), Some(meta::Expr::Float(meta::Float::F64(-1.5))));
+    assert_eq!(default("tiny"), Some(meta::Expr::Float(meta::Float::F64(1e-9))));
+    assert_eq!(default("huge"), Some(meta::Expr::Float(meta::Float::F64(1e20))));
+    assert_eq!(default("suffixed"), Some(meta::Expr::Float(meta::Float::F32(-3.0e-4))));
+
+    // Templates with these values have to be valid and load the same values.
+    #[cfg(feature = "test-util")]
+    confique::test::roundtrip_all_formats::<Conf>();
+}
@@ -20,8 +20,13 @@ fn string_to_u32() {
             meta::Field {
                 name: "bar",
                 doc: &[" A nice doc comment."],
+                hidden_in_template: false,
                 kind: meta::FieldKind::Leaf {
                     env: None,
+                    has_parse_env: false,
+                    has_deserialize_with: false,
+                    example: None,
+                    secret: false,
                     kind: meta::LeafKind::Required {
                         default: Some(meta::Expr::Map(&[
                             meta::MapEntry {
@@ -37,8 +42,65 @@ fn string_to_u32() {
                 },
             },
         ],
+        preambles: meta::Preambles::NONE,
     });
 
     let def = Foo::builder().load().unwrap();
     assert_eq!(def.bar, HashMap::from([("peter".into(), 3), ("anna".into(), 27)]));
 }
+
+mod ordering {
+    use super::*;
+
+    #[derive(Config)]
+    #[allow(dead_code)]
+    pub struct Conf {
+        #[config(default = { "zebra": 1, "apple": 2, "mango": 3 })]
+        pub fruits: HashMap<String, u32>,
+    }
+}
+
+/// Map defaults are rendered in source order, not the (random) iteration order
+/// of `HashMap` or sorted.
+#[test]
+#[cfg(any(feature = "toml", feature = "yaml", feature = "json5"))]
+fn template_source_order() {
+    let assert_order = |template: String| {
+        let pos = |key| template.find(key).unwrap_or_else(|| panic!("{key} missing:\n{template}"));
+        assert!(pos("zebra") < pos("apple"), "{template}");
+        assert!(pos("apple") < pos("mango"), "{template}");
+    };
+
+    #[cfg(feature = "toml")]
+    {
+        let template = confique::toml::template::<ordering::Conf>(Default::default());
+        assert!(template.contains("{ zebra = 1, apple = 2, mango = 3 }"), "{template}");
+        assert_order(template);
+    }
+    #[cfg(feature = "yaml")]
+    assert_order(confique::yaml::template::<ordering::Conf>(Default::default()));
+    #[cfg(feature = "json5")]
+    assert_order(confique::json5::template::<ordering::Conf>(Default::default()));
+}
+
+/// Values of `HashMap` fields are rendered sorted by key in templates with
+/// values, regardless of the map's iteration order.
+#[test]
+#[cfg(feature = "toml")]
+fn template_with_values_deterministic() {
+    #[derive(Config, serde::Serialize)]
+    struct Conf {
+        map: HashMap<String, u32>,
+    }
+
+    let render = || {
+        // Every `HashMap` has its own random iteration order.
+        let map = (0..20).map(|i| (format!("k{i:02}"), i)).collect();
+        confique::toml::template_with_values(&Conf { map }, Default::default()).unwrap()
+    };
+    let first = render();
+    assert!(first.contains("k00 = 0, k01 = 1, k02 = 2"), "{first}");
+    for _ in 0..10 {
+        assert_eq!(render(), first);
+    }
+}
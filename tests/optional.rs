@@ -0,0 +1,96 @@
+use pretty_assertions::assert_eq;
+
+use confique::{Config, Partial};
+
+
+#[derive(Config)]
+#[config(partial_attr(derive(Debug, PartialEq)))]
+struct Conf {
+    list: Option<Vec<u32>>,
+}
+
+type PartialConf = <Conf as Config>::Partial;
+
+fn load(toml: &str) -> PartialConf {
+    toml::from_str(toml).unwrap()
+}
+
+#[test]
+fn collection_states() {
+    assert_eq!(load("").list, None);
+    assert_eq!(load("list = []").list, Some(vec![]));
+    assert_eq!(load("list = [1, 2]").list, Some(vec![1, 2]));
+
+    assert_eq!(Conf::from_partial(load("")).unwrap().list, None);
+    assert_eq!(Conf::from_partial(load("list = []")).unwrap().list, Some(vec![]));
+}
+
+#[test]
+fn empty_collection_is_not_a_gap() {
+    let merged = load("list = []").with_fallback(load("list = [3]"));
+    assert_eq!(merged.list, Some(vec![]));
+
+    let merged = load("").with_fallback(load("list = [3]"));
+    assert_eq!(merged.list, Some(vec![3]));
+
+    let conf = Conf::builder()
+        .preloaded(load("list = []"))
+        .preloaded(load("list = [3]"))
+        .load()
+        .unwrap();
+    assert_eq!(conf.list, Some(vec![]));
+}
+
+
+mod enum_default {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    pub enum LogLevel {
+        Error,
+        Info,
+        Debug,
+    }
+
+    #[derive(Config)]
+    #[config(partial_attr(derive(Debug, PartialEq)))]
+    pub struct Conf {
+        /// Default log level.
+        #[config(default = "info")]
+        pub level: LogLevel,
+
+        /// Log level of the audit log. Disabled if unset.
+        pub audit_level: Option<LogLevel>,
+    }
+}
+
+#[test]
+fn enum_default() {
+    use enum_default::{Conf, LogLevel};
+    type PartialConf = <Conf as Config>::Partial;
+
+    let defaults = PartialConf::default_values();
+    assert_eq!(defaults.level, Some(LogLevel::Info));
+    assert_eq!(defaults.audit_level, None);
+
+    let conf = Conf::builder().preloaded(toml::from_str("").unwrap()).load().unwrap();
+    assert_eq!(conf.level, LogLevel::Info);
+    assert_eq!(conf.audit_level, None);
+
+    let partial = toml::from_str("audit_level = \"debug\"").unwrap();
+    let conf = Conf::builder().preloaded(partial).load().unwrap();
+    assert_eq!(conf.audit_level, Some(LogLevel::Debug));
+
+    let template = confique::toml::template::<Conf>(Default::default());
+    assert_eq!(template, concat!(
+        "# Default log level.\n",
+        "#\n",
+        "# Default value: \"info\"\n",
+        "#level = \"info\"\n",
+        "\n",
+        "# Log level of the audit log. Disabled if unset.\n",
+        "#audit_level =\n",
+    ));
+}
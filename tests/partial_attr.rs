@@ -0,0 +1,98 @@
+use pretty_assertions::assert_eq;
+use serde::Deserialize;
+
+use confique::{Config, Partial};
+
+
+fn load<C: Config>(toml: &str) -> Result<C, String> {
+    let partial: C::Partial = toml::from_str(toml).map_err(|e| e.message().to_owned())?;
+    C::from_partial(partial.with_fallback(Partial::default_values())).map_err(|e| e.to_string())
+}
+
+mod renamed {
+    use super::*;
+
+    #[derive(Config)]
+    #[config(partial_attr(serde(rename_all = "kebab-case")))]
+    pub struct Conf {
+        #[config(default = 8080)]
+        pub listen_port: u16,
+        #[config(env = "PARTIAL_ATTR_MAX_CONNECTIONS")]
+        pub max_connections: u32,
+    }
+}
+
+#[test]
+fn rename_all() {
+    use renamed::Conf;
+
+    let conf = load::<Conf>("listen-port = 80\nmax-connections = 5").unwrap();
+    assert_eq!((conf.listen_port, conf.max_connections), (80, 5));
+
+    // The original name is an unknown key now. Note that confique itself
+    // (e.g. in errors) still uses the field name.
+    assert_eq!(
+        load::<Conf>("max_connections = 5").err().unwrap(),
+        "required configuration value is missing: 'max_connections'",
+    );
+
+    // Env variables are not affected by serde attributes.
+    std::env::set_var("PARTIAL_ATTR_MAX_CONNECTIONS", "7");
+    assert_eq!(Conf::builder().env().load().unwrap().max_connections, 7);
+}
+
+mod custom_deserialize {
+    use super::*;
+
+    /// Accepts either just the host name or a full table for the layer.
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    pub enum Raw {
+        Host(String),
+        Table {
+            host: Option<String>,
+            port: Option<u16>,
+        },
+    }
+
+    #[derive(Config, Debug)]
+    #[config(partial_attr(serde(from = "Raw")))]
+    pub struct Server {
+        pub host: String,
+        #[config(default = 443)]
+        pub port: u16,
+    }
+
+    impl From<Raw> for PartialServer {
+        fn from(raw: Raw) -> Self {
+            match raw {
+                Raw::Host(host) => Self { host: Some(host), port: None },
+                Raw::Table { host, port } => Self { host, port },
+            }
+        }
+    }
+
+    pub type PartialServer = <Server as Config>::Partial;
+
+    #[derive(Config, Debug)]
+    pub struct Conf {
+        #[config(nested)]
+        pub server: Server,
+    }
+}
+
+#[test]
+fn container_from() {
+    use custom_deserialize::Conf;
+
+    let conf = load::<Conf>("server = \"example.com\"").unwrap();
+    assert_eq!((conf.server.host.as_str(), conf.server.port), ("example.com", 443));
+
+    let conf = load::<Conf>("[server]\nhost = \"example.org\"\nport = 8443").unwrap();
+    assert_eq!((conf.server.host.as_str(), conf.server.port), ("example.org", 8443));
+
+    assert_eq!(
+        load::<Conf>("[server]\nport = 8443").err().unwrap(),
+        "required configuration value is missing: 'server.host'",
+    );
+}
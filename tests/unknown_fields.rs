@@ -0,0 +1,118 @@
+use confique::Config;
+
+
+#[derive(Config)]
+#[allow(dead_code)]
+struct Conf {
+    #[config(default = 1)]
+    level: u32,
+
+    #[config(nested)]
+    strict: Strict,
+
+    #[config(nested)]
+    lenient: Lenient,
+}
+
+#[derive(Config)]
+#[config(deny_unknown_fields)]
+#[allow(dead_code)]
+struct Strict {
+    #[config(default = 2)]
+    port: u16,
+
+    #[config(nested)]
+    inner: Lenient,
+}
+
+#[derive(Config)]
+#[allow(dead_code)]
+struct Lenient {
+    #[config(default = 3)]
+    size: u32,
+}
+
+type PartialConf = <Conf as Config>::Partial;
+
+fn load(toml: &str) -> Result<PartialConf, String> {
+    toml::from_str(toml).map_err(|e| e.message().to_owned())
+}
+
+#[test]
+fn deny_unknown_fields_nested() {
+    // Known keys are fine everywhere.
+    let partial = load("level = 5\n[strict]\nport = 8\n[strict.inner]\nsize = 9").unwrap();
+    assert_eq!(partial.strict.port, Some(8));
+    assert_eq!(partial.strict.inner.size, Some(9));
+
+    // Unknown keys are only rejected in the struct with the attribute...
+    let err = load("[strict]\nport = 8\nprot = 9").err().unwrap();
+    assert_eq!(err, "unknown field `prot`, expected `port` or `inner`");
+
+    // ... but not in the parent, a sibling or a nested struct without it.
+    assert!(load("typo = 1").is_ok());
+    assert!(load("[lenient]\ntypo = 1").is_ok());
+    assert!(load("[strict.inner]\ntypo = 1").is_ok());
+}
+
+#[test]
+fn require_known_keys() {
+    let dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR"));
+    let write = |name: &str, content: &str| {
+        let path = dir.join(name);
+        std::fs::write(&path, content).unwrap();
+        path
+    };
+
+    let unrelated = write("require_known_keys_unrelated.toml", "[package]\nname = \"foo\"\n");
+    let valid = write("require_known_keys_valid.toml", "typo = 1\nlevel = 5\n");
+    let empty = write("require_known_keys_empty.toml", "# only a comment\n");
+
+    // Without the flag, the unrelated file is silently ignored.
+    assert!(Conf::builder().file(&unrelated).load().is_ok());
+
+    let err = Conf::builder().require_known_keys().file(&unrelated).load().err().unwrap();
+    assert_eq!(err.to_string(), format!(
+        "configuration file '{}' does not contain any known configuration values \
+            (is it the right file?)",
+        unrelated.display(),
+    ));
+
+    // Files with at least one known key, without keys and missing files are fine.
+    let conf = Conf::builder().require_known_keys().file(&valid).load().unwrap();
+    assert_eq!(conf.level, 5);
+    assert!(Conf::builder().require_known_keys().file(&empty).load().is_ok());
+    assert!(Conf::builder()
+        .require_known_keys()
+        .file(dir.join("require_known_keys_missing.toml"))
+        .load()
+        .is_ok());
+}
+
+#[test]
+fn deny_unknown_fields_partial_layers() {
+    let dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR"));
+    let path = dir.join("deny_unknown_fields_partial_layers.toml");
+
+    // Known keys may be absent in any layer, including whole sections.
+    std::fs::write(&path, "[strict]\nport = 8\n").unwrap();
+    let conf = Conf::builder()
+        .file(&path)
+        .lookup_fn(|path| (path == "strict.inner.size").then(|| "7".into()))
+        .preloaded(load("").unwrap())
+        .load()
+        .unwrap();
+    assert_eq!(conf.strict.port, 8);
+    assert_eq!(conf.strict.inner.size, 7);
+    assert_eq!(conf.lenient.size, 3);
+
+    // One known and one unknown key: the error names the unknown one.
+    std::fs::write(&path, "[strict]\nport = 8\ncolour = \"red\"\n").unwrap();
+    let err = Conf::builder().file(&path).load().err().unwrap();
+    let msg = err.display_chain().to_string();
+    assert!(msg.starts_with(&format!(
+        "failed to deserialize configuration from file '{}'",
+        path.display(),
+    )), "{msg}");
+    assert!(msg.contains("unknown field `colour`, expected `port` or `inner`"), "{msg}");
+}
@@ -526,6 +526,132 @@ fn struct_validation() {
     assert_err_contains(load("foo = 123\nbar=27"), "exactly one of foo and bar must be set");
 }
 
+mod nested_validation {
+    use super::*;
+
+    #[derive(Config, Debug)]
+    pub struct Conf {
+        #[config(nested, validate = Self::check_primary)]
+        pub primary: Db,
+
+        #[config(nested, validate(replica.pool_size <= 4, "replica pool too large"))]
+        pub replica: Db,
+    }
+
+    impl Conf {
+        fn check_primary(db: &Db) -> Result<(), String> {
+            if db.pool_size < 8 {
+                return Err(format!("primary pool too small: {}", db.pool_size));
+            }
+            Ok(())
+        }
+    }
+
+    #[derive(Config, Debug)]
+    #[config(validate = Self::validate)]
+    pub struct Db {
+        #[config(default = 8)]
+        pub pool_size: u32,
+    }
+
+    impl Db {
+        fn validate(&self) -> Result<(), &'static str> {
+            if self.pool_size == 0 {
+                return Err("pool size must not be 0");
+            }
+            Ok(())
+        }
+    }
+}
+
+#[test]
+fn nested_validation() {
+    use nested_validation::Conf;
+
+    let load = |s: &str| {
+        let partial = toml::from_str::<<Conf as Config>::Partial>(s).unwrap();
+        Conf::from_partial(partial.with_fallback(Partial::default_values()))
+    };
+
+    let conf = load("replica.pool_size = 2").unwrap();
+    assert_eq!((conf.primary.pool_size, conf.replica.pool_size), (8, 2));
+
+    // The same type is validated differently depending on the field.
+    assert_eq!(
+        load("primary.pool_size = 4\nreplica.pool_size = 2").unwrap_err().to_string(),
+        "config validation of `Conf::primary` failed: primary pool too small: 4",
+    );
+    assert_eq!(
+        load("").unwrap_err().to_string(),
+        "config validation of `Conf::replica` failed: replica pool too large",
+    );
+
+    // The nested type's own validation runs first.
+    assert_eq!(
+        load("primary.pool_size = 0").unwrap_err().to_string(),
+        "config validation of `Db` failed: pool size must not be 0",
+    );
+}
+
+mod warnings {
+    use super::*;
+
+    #[derive(Config)]
+    #[config(warn(*port < 1024, "ports below 1024 usually require root"))]
+    #[config(warn(
+        *port == 80 && tls.cert.is_some(),
+        "TLS on port 80 is unusual",
+    ))]
+    pub struct Conf {
+        #[config(default = 8080)]
+        pub port: u16,
+
+        #[config(nested)]
+        pub tls: Tls,
+    }
+
+    #[derive(Config)]
+    #[config(warn(cert.is_some() && !*enabled, "certificate is ignored as TLS is disabled"))]
+    pub struct Tls {
+        pub cert: Option<String>,
+        #[config(default = true)]
+        pub enabled: bool,
+    }
+}
+
+#[test]
+fn warnings() {
+    use warnings::Conf;
+
+    let load = |s: &str| {
+        let partial = toml::from_str::<<Conf as Config>::Partial>(s).unwrap();
+        Conf::builder().preloaded(partial).load_with_warnings().unwrap()
+    };
+    let messages = |s: &str| {
+        load(s).1.iter().map(|w| w.to_string()).collect::<Vec<_>>()
+    };
+
+    let (conf, warnings) = load("");
+    assert_eq!(conf.port, 8080);
+    assert!(warnings.is_empty());
+
+    assert_eq!(messages("port = 443"), ["ports below 1024 usually require root"]);
+    assert_eq!(messages("port = 80\ntls.cert = \"a.pem\""), [
+        "ports below 1024 usually require root",
+        "TLS on port 80 is unusual",
+    ]);
+
+    let (_, warnings) = load("tls.cert = \"a.pem\"\ntls.enabled = false");
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].path(), "tls");
+    assert_eq!(warnings[0].message(), "certificate is ignored as TLS is disabled");
+    assert_eq!(warnings[0].to_string(), "tls: certificate is ignored as TLS is disabled");
+
+    // `load` ignores warnings.
+    let partial = toml::from_str("port = 1").unwrap();
+    assert_eq!(Conf::builder().preloaded(partial).load().unwrap().port, 1);
+}
+
 #[track_caller]
 fn assert_err_contains<T, E: std::fmt::Display>(r: Result<T, E>, expected: &str) {
     let e = r.map(|_| ()).unwrap_err();
@@ -534,3 +660,55 @@ fn assert_err_contains<T, E: std::fmt::Display>(r: Result<T, E>, expected: &str)
         panic!("expected error msg to contain '{expected}', but it doesn't: \n{s}");
     }
 }
+
+#[test]
+#[cfg(feature = "test-util")]
+fn assert_defaults_valid_ok() {
+    #[derive(Config)]
+    #[allow(dead_code)]
+    #[config(validate = Self::validate)]
+    struct Conf {
+        #[config(default = 1, validate(*foo != 1234, "ugly number"))]
+        foo: u32,
+        bar: Option<u32>,
+    }
+
+    impl Conf {
+        fn validate(&self) -> Result<(), &'static str> {
+            Err("never valid")
+        }
+    }
+
+    // Only leaf defaults are checked if required values are missing.
+    #[derive(Config)]
+    #[allow(dead_code)]
+    struct Incomplete {
+        #[config(default = 1, validate(*foo != 1234, "ugly number"))]
+        foo: u32,
+        bar: u32,
+    }
+
+    confique::test::assert_defaults_valid::<Incomplete>();
+
+    let res = std::panic::catch_unwind(confique::test::assert_defaults_valid::<Conf>);
+    let msg = res.unwrap_err().downcast::<String>().unwrap();
+    assert_eq!(
+        *msg,
+        "default values of `Conf` are invalid: config validation of `Conf` failed: never valid",
+    );
+}
+
+#[test]
+#[cfg(feature = "test-util")]
+#[should_panic(expected = "default config value for `Conf::foo` cannot be \
+    deserialized: Error(\"validation failed: ugly number\")")]
+fn assert_defaults_valid_invalid_field() {
+    #[derive(Config)]
+    #[allow(dead_code)]
+    struct Conf {
+        #[config(default = 1234, validate(*foo != 1234, "ugly number"))]
+        foo: u32,
+    }
+
+    confique::test::assert_defaults_valid::<Conf>();
+}